@@ -0,0 +1,385 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{BatchArgs, BatchLayout, OutputFormat, PdfEngine};
+use crate::output;
+use crate::qr_generator;
+use crate::sheet::{SheetEntry, SheetLayout};
+use crate::wifi_utils::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType};
+
+/// One network of an inventory file. Only `ssid` is required.
+#[derive(Deserialize, Debug)]
+struct BatchEntry {
+    ssid: String,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    security: Option<String>, // Parsed like --security; defaults to WPA, or nopass without a password.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    hidden: bool,
+    #[serde(default)]
+    title: Option<String>, // PDF title, defaults to the SSID.
+    #[serde(default)]
+    output: Option<String>, // Output filename, defaults to '<ssid>_qrcode'.
+    #[serde(default)]
+    format: Option<String>, // Overrides --format for this entry.
+    // 802.1X settings, only used with the enterprise security type.
+    #[serde(default)]
+    eap: Option<String>,
+    #[serde(default)]
+    phase2: Option<String>,
+    #[serde(default)]
+    identity: Option<String>,
+    #[serde(default)]
+    anonymous_identity: Option<String>,
+}
+
+/// JSON inventories may be a plain array or an object with a `networks` array (like TOML).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonInventory {
+    List(Vec<BatchEntry>),
+    Table { networks: Vec<BatchEntry> },
+}
+
+#[derive(Deserialize)]
+struct TomlInventory {
+    networks: Vec<BatchEntry>,
+}
+
+// Accepts booleans as well as the strings common in spreadsheets ("yes", "1", "true", empty).
+fn deserialize_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Number(u64), // CSV cells such as "1" are read as numbers.
+        Text(String),
+    }
+
+    match Flag::deserialize(deserializer)? {
+        Flag::Bool(value) => Ok(value),
+        Flag::Number(1) => Ok(true),
+        Flag::Number(0) => Ok(false),
+        Flag::Number(other) => Err(serde::de::Error::custom(format!("invalid boolean value '{}'", other))),
+        Flag::Text(text) => match text.trim().to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok(true),
+            "false" | "no" | "n" | "0" | "" => Ok(false),
+            other => Err(serde::de::Error::custom(format!("invalid boolean value '{}'", other))),
+        },
+    }
+}
+
+// Reads all entries of an inventory file, choosing the parser by file extension.
+fn read_inventory(path: &Path) -> Result<Vec<BatchEntry>, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "csv" => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_path(path)
+                .map_err(|e| format!("Failed to open inventory file '{}': {}", path.display(), e))?;
+            reader
+                .deserialize()
+                .enumerate()
+                .map(|(i, row)| row.map_err(|e| format!("Invalid row {} in '{}': {}", i + 1, path.display(), e)))
+                .collect()
+        }
+        "json" | "toml" => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read inventory file '{}': {}", path.display(), e))?;
+            if extension == "json" {
+                match serde_json::from_str::<JsonInventory>(&content) {
+                    Ok(JsonInventory::List(entries)) | Ok(JsonInventory::Table { networks: entries }) => Ok(entries),
+                    Err(e) => Err(format!("Failed to parse JSON inventory '{}': {}", path.display(), e)),
+                }
+            } else {
+                toml::from_str::<TomlInventory>(&content)
+                    .map(|inventory| inventory.networks)
+                    .map_err(|e| format!("Failed to parse TOML inventory '{}': {}", path.display(), e))
+            }
+        }
+        _ => Err(format!(
+            "Unsupported inventory file '{}'. Use a .csv, .json or .toml file.",
+            path.display()
+        )),
+    }
+}
+
+// Builds the 802.1X credentials of an enterprise entry.
+fn enterprise_credentials(entry: &BatchEntry) -> Result<EnterpriseCredentials, String> {
+    let eap_name = entry.eap.as_deref().ok_or("Enterprise networks require an 'eap' method")?;
+    let eap_method = EapMethod::from_name(eap_name).ok_or(format!("Unknown EAP method '{}'", eap_name))?;
+    let phase2_method = match entry.phase2.as_deref() {
+        Some(name) => Some(Phase2Method::from_name(name).ok_or(format!("Unknown phase 2 method '{}'", name))?),
+        None => None,
+    };
+    Ok(EnterpriseCredentials {
+        eap_method,
+        phase2_method,
+        identity: entry.identity.clone(),
+        anonymous_identity: entry.anonymous_identity.clone(),
+    })
+}
+
+// Builds the WIFI payload of an entry.
+fn entry_qr_data(entry: &BatchEntry) -> Result<String, Box<dyn Error>> {
+    let password = entry.password.clone().unwrap_or_default();
+    let security_type = match entry.security.as_deref() {
+        Some(security) => security.parse::<SecurityType>()?,
+        None if password.is_empty() => SecurityType::Open,
+        None => SecurityType::WpaPersonal,
+    };
+    let enterprise = if security_type == SecurityType::Enterprise {
+        Some(enterprise_credentials(entry)?)
+    } else {
+        None
+    };
+
+    Ok(qr_generator::generate_qr_code_data(
+        &entry.ssid,
+        &password,
+        security_type,
+        entry.hidden,
+        enterprise.as_ref(),
+    ))
+}
+
+// Returns the title printed above the QR code of an entry.
+fn entry_title(entry: &BatchEntry, pdf_engine: PdfEngine) -> String {
+    entry.title.clone().unwrap_or_else(|| output::default_title(&entry.ssid, pdf_engine))
+}
+
+// Returns the base filename of an entry. The 'output' column is a filename, not a path.
+fn entry_base_name(entry: &BatchEntry) -> Result<String, String> {
+    match entry.output.as_deref() {
+        Some(name) if name.contains(['/', '\\']) || name == "." || name == ".." => {
+            Err(format!("Invalid output filename '{}': paths are not allowed, use --output-dir", name))
+        }
+        Some(name) => Ok(output::strip_output_extension(name)),
+        None => Ok(output::default_base_name(&entry.ssid)),
+    }
+}
+
+// Generates the file for one entry and returns its path.
+// Paths already written by previous entries are rejected instead of being overwritten.
+fn generate_entry(
+    entry: &BatchEntry,
+    args: &BatchArgs,
+    output_dir: &Path,
+    pdf_engine: Option<PdfEngine>,
+    used_paths: &mut HashSet<PathBuf>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let qr_data = entry_qr_data(entry)?;
+    let format = match entry.format.as_deref() {
+        Some(name) => OutputFormat::from_str(name, true).map_err(|_| format!("Unknown output format '{}'", name))?,
+        None => args.format,
+    };
+    let pdf_engine = match (format, pdf_engine) {
+        (OutputFormat::Pdf, Some(engine)) => engine,
+        (OutputFormat::Pdf, None) => return Err("No PDF engine available".into()),
+        _ => PdfEngine::Native, // Not used for image formats.
+    };
+
+    let output_path = output_dir.join(format!("{}.{}", entry_base_name(entry)?, format.extension()));
+    if !used_paths.insert(output_path.clone()) {
+        return Err(format!(
+            "Output file '{}' is already used by another entry; set a unique 'output' filename",
+            output_path.display()
+        ).into());
+    }
+
+    output::write_qr_code(&qr_data, format, &output_path, &entry_title(entry, pdf_engine), pdf_engine, args.design.as_ref())?;
+    if args.verify {
+        output::verify_qr_code(&qr_data, format, &output_path)?;
+    }
+    Ok(output_path)
+}
+
+// Prints the entries that could not be generated and turns them into the result of the batch.
+fn report_failures(failures: &[(usize, String, String)], total: usize) -> Result<(), Box<dyn Error>> {
+    for (row, ssid, error) in failures {
+        println!("  Failed [{}] {}: {}", row, ssid, error);
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} of {} networks could not be generated", failures.len(), total).into())
+    }
+}
+
+// Combines all entries into one PDF (booklet or card sheets) named after the inventory file.
+fn run_sheet(args: &BatchArgs, entries: &[BatchEntry], output_dir: &Path) -> Result<(), Box<dyn Error>> {
+    if args.format != OutputFormat::Pdf {
+        return Err("Booklet and card layouts can only be generated as PDF.".into());
+    }
+    let (layout, suffix) = match args.layout {
+        BatchLayout::Cards => (SheetLayout::cards(args.paper, args.grid.0, args.grid.1)?, "cards"),
+        _ => (SheetLayout::booklet(args.paper), "booklet"),
+    };
+    let pdf_engine = output::resolve_pdf_engine(args.pdf_engine, args.design.is_some())?;
+
+    let mut sheet_entries = Vec::new();
+    let mut failures = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        match entry_qr_data(entry) {
+            Ok(qr_data) => sheet_entries.push(SheetEntry { title: entry_title(entry, pdf_engine), qr_data }),
+            Err(e) => {
+                eprintln!("[{}] {}: Error: {}", i + 1, entry.ssid, e);
+                failures.push((i + 1, entry.ssid.clone(), e.to_string()));
+            }
+        }
+    }
+
+    if !sheet_entries.is_empty() {
+        let stem = args.file.file_stem().and_then(|s| s.to_str()).unwrap_or("networks");
+        let output_path = output_dir.join(format!("{}_{}.pdf", stem, suffix));
+        output::write_sheet(&sheet_entries, &layout, &output_path, pdf_engine, args.design.as_ref())?;
+        println!("Successfully generated {} with {} networks: {}", suffix, sheet_entries.len(), output_path.display());
+    }
+
+    println!(
+        "\nBatch summary: {} of {} networks added, {} failed.",
+        sheet_entries.len(),
+        entries.len(),
+        failures.len()
+    );
+    report_failures(&failures, entries.len())
+}
+
+/// Generates the QR codes of all entries of an inventory file and prints a summary: one file per entry,
+/// or one combined PDF for the booklet and card layouts.
+/// Failing entries are reported and skipped; the result is an error if any entry failed.
+pub fn run(args: &BatchArgs) -> Result<(), Box<dyn Error>> {
+    let entries = read_inventory(&args.file)?;
+    if entries.is_empty() {
+        println!("No networks found in '{}'.", args.file.display());
+        return Ok(());
+    }
+
+    let output_dir = match args.output_dir {
+        Some(ref dir) => dir.clone(),
+        None => output::default_output_dir()?,
+    };
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create output directory '{}': {}", output_dir.display(), e))?;
+
+    if args.layout != BatchLayout::Files {
+        return run_sheet(args, &entries, &output_dir);
+    }
+
+    // The PDF engine is resolved once for all entries; its absence only fails PDF entries.
+    let needs_pdf = entries.iter().any(|entry| match entry.format.as_deref() {
+        Some(name) => name.eq_ignore_ascii_case("pdf"),
+        None => args.format == OutputFormat::Pdf,
+    });
+    let pdf_engine = if needs_pdf {
+        match output::resolve_pdf_engine(args.pdf_engine, args.design.is_some()) {
+            Ok(engine) => Some(engine),
+            Err(err_msg) => {
+                eprintln!("{}", err_msg);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut failures = Vec::new();
+    let mut used_paths = HashSet::new();
+    for (i, entry) in entries.iter().enumerate() {
+        match generate_entry(entry, args, &output_dir, pdf_engine, &mut used_paths) {
+            Ok(path) => println!("[{}] {}: {}", i + 1, entry.ssid, path.display()),
+            Err(e) => {
+                eprintln!("[{}] {}: Error: {}", i + 1, entry.ssid, e);
+                failures.push((i + 1, entry.ssid.clone(), e.to_string()));
+            }
+        }
+    }
+
+    println!(
+        "\nBatch summary: {} of {} QR codes generated successfully, {} failed.",
+        entries.len() - failures.len(),
+        entries.len(),
+        failures.len()
+    );
+    report_failures(&failures, entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/batch").join(name)
+    }
+
+    fn assert_inventory(entries: &[BatchEntry]) {
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].ssid, "Office");
+        assert_eq!(entries[0].password.as_deref(), Some("pa;ss"));
+        assert!(!entries[0].hidden);
+
+        assert_eq!(entries[1].ssid, "Lab");
+        assert_eq!(entries[1].security.as_deref(), Some("WPA3"));
+        assert_eq!(entries[1].output.as_deref(), Some("lab.png"));
+        assert!(entries[1].hidden);
+
+        assert_eq!(entries[2].ssid, "Corp");
+        assert_eq!(entries[2].eap.as_deref(), Some("PEAP"));
+        assert_eq!(entries[2].identity.as_deref(), Some("alice"));
+        assert_eq!(
+            entry_qr_data(&entries[2]).unwrap(),
+            "WIFI:S:Corp;T:WPA2-EAP;P:s3cret;E:PEAP;PH2:MSCHAPV2;I:alice;;"
+        );
+    }
+
+    #[test]
+    fn read_inventory_parses_csv_json_and_toml() {
+        for name in ["networks.csv", "networks.json", "networks.toml"] {
+            assert_inventory(&read_inventory(&fixture(name)).unwrap());
+        }
+    }
+
+    #[test]
+    fn read_inventory_accepts_json_arrays() {
+        let entries: Vec<BatchEntry> = match serde_json::from_str(r#"[{"ssid": "Guest"}]"#).unwrap() {
+            JsonInventory::List(entries) | JsonInventory::Table { networks: entries } => entries,
+        };
+        assert_eq!(entries[0].ssid, "Guest");
+        assert_eq!(entry_qr_data(&entries[0]).unwrap(), "WIFI:S:Guest;T:nopass;;");
+    }
+
+    #[test]
+    fn hidden_flag_accepts_booleans_and_spreadsheet_values() {
+        let hidden = |value: &str| {
+            serde_json::from_str::<BatchEntry>(&format!(r#"{{"ssid": "Net", "hidden": {}}}"#, value)).map(|entry| entry.hidden)
+        };
+        for value in ["true", "1", r#""yes""#, r#""Y""#, r#""1""#, r#"" TRUE ""#] {
+            assert!(hidden(value).unwrap(), "{}", value);
+        }
+        for value in ["false", "0", r#""no""#, r#""0""#, r#""""#] {
+            assert!(!hidden(value).unwrap(), "{}", value);
+        }
+        assert!(hidden(r#""maybe""#).is_err());
+    }
+
+    #[test]
+    fn entry_base_name_rejects_paths() {
+        let entry = |output: &str| BatchEntry {
+            output: Some(output.to_string()),
+            ..serde_json::from_str(r#"{"ssid": "Office Net"}"#).unwrap()
+        };
+        assert_eq!(entry_base_name(&entry("office.pdf")).unwrap(), "office");
+        assert!(entry_base_name(&entry("../office")).is_err());
+        assert!(entry_base_name(&entry("sub/office")).is_err());
+        assert!(entry_base_name(&entry(r"sub\office")).is_err());
+        assert!(entry_base_name(&entry("..")).is_err());
+    }
+}
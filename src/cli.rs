@@ -1,5 +1,7 @@
 use std::path::PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::wifi_utils::SecurityType;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -38,8 +40,244 @@ pub struct Args {
     #[clap(long, group = "output_mode")]
     pub svg: bool,
 
+    /// Mark the network as hidden (non-broadcast SSID) in the QR code.
+    /// Hidden networks detected on the system are marked automatically.
+    #[clap(long)]
+    pub hidden: bool,
+
+    /// Generate the QR code for this SSID instead of prompting for a network.
+    /// Details of a matching known network (password, security type) are used if available.
+    #[clap(long)]
+    pub ssid: Option<String>,
+
+    /// Generate the QR code for the network this system is currently connected to.
+    #[clap(long, conflicts_with = "ssid")]
+    pub current: bool,
+
+    /// Security type of the network (e.g., WPA, WPA3, WPA2/WPA3, WEP, OWE, nopass).
+    /// Overrides the automatically detected security type.
+    #[clap(long)]
+    pub security: Option<SecurityType>,
+
+    /// Read the password from the first line of this file.
+    #[clap(long, group = "password_source")]
+    pub password_file: Option<PathBuf>,
+
+    /// Read the password from this environment variable.
+    #[clap(long, group = "password_source")]
+    pub password_env: Option<String>,
+
+    /// Read the password from stdin.
+    #[clap(long, group = "password_source")]
+    pub password_stdin: bool,
+
+    /// Read the known networks from these sources instead of probing the sources of this system
+    /// (e.g. 'nmcli', 'iwd', 'netplan'; comma-separated or repeated). Networks of several sources are merged.
+    #[clap(long, value_delimiter = ',')]
+    pub source: Vec<String>,
+
+    /// Select the network from a wpa_supplicant.conf file instead of the networks known to the system.
+    #[clap(long)]
+    pub from_wpa_supplicant: Option<PathBuf>,
+
+    /// Select the network from a hostapd.conf file; each bss= section is listed as a separate network.
+    #[clap(long)]
+    pub from_hostapd: Option<PathBuf>,
+
+    /// Select the network from an OpenWrt wireless configuration (/etc/config/wireless).
+    #[clap(long)]
+    pub from_uci: Option<PathBuf>,
+
+    /// Select the network from a netplan file or directory (e.g. /etc/netplan).
+    #[clap(long)]
+    pub from_netplan: Option<PathBuf>,
+
+    /// Select the network from Windows WLAN profile XML exports (a file or a directory of *.xml files).
+    #[clap(long)]
+    pub from_windows_xml: Option<PathBuf>,
+
+    /// Select the network from the Wi-Fi payloads of an Apple configuration profile (.mobileconfig) or Wi-Fi plist.
+    #[clap(long)]
+    pub from_mobileconfig: Option<PathBuf>,
+
+    /// Select the network from an Android WifiConfigStore.xml backup.
+    #[clap(long)]
+    pub from_android: Option<PathBuf>,
+
+    /// Title for the PDF (defaults to the SSID).
+    #[clap(long)]
+    pub title: Option<String>,
+
+    /// Filename for the generated file (defaults to '<ssid>_qrcode').
+    #[clap(long)]
+    pub filename: Option<String>,
+
+    /// Never prompt for input; fail with exit code 2 if a required value is missing.
+    /// Enabled automatically when stdin is not a terminal.
+    #[clap(long)]
+    pub non_interactive: bool,
+
     /// Specify a custom LaTeX design file (e.g., custom.tex) for PDF output.
-    /// This flag is ignored if the output format is not PDF.
+    /// This flag is ignored if the output format is not PDF. Requires the pdflatex engine.
+    #[clap(long)]
+    pub design: Option<String>,
+
+    /// PDF engine to use. Defaults to pdflatex if installed, otherwise the built-in native engine.
+    #[clap(long, value_enum)]
+    pub pdf_engine: Option<PdfEngine>,
+
+    /// Decode the generated PNG/JPG file and fail if it does not scan back to the intended network.
+    #[clap(long)]
+    pub verify: bool,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate QR codes for the networks listed in a CSV, JSON or TOML inventory file.
+    ///
+    /// By default one file is generated per network. With --layout booklet or --layout cards,
+    /// all networks are combined into one PDF named after the inventory file.
+    /// Each entry has the fields ssid (required), password, security, hidden, title, output and format.
+    /// Enterprise networks additionally use eap, phase2, identity and anonymous_identity.
+    /// TOML files list the entries as [[networks]] tables; JSON files use an array or a "networks" array.
+    Batch(BatchArgs),
+
+    /// Decode the Wi-Fi QR codes of a PNG or JPG image and print the networks they contain.
+    Decode(DecodeArgs),
+
+    /// Create a NetworkManager connection from the Wi-Fi QR code of a PNG or JPG image (Linux only).
+    Connect(ConnectArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    /// Inventory file (.csv, .json or .toml).
+    pub file: PathBuf,
+
+    /// Directory for the generated files. Defaults to the Desktop.
+    #[clap(long, short)]
+    pub output_dir: Option<PathBuf>,
+
+    /// File format for entries without a format field.
+    #[clap(long, value_enum, default_value_t = OutputFormat::Pdf)]
+    pub format: OutputFormat,
+
+    /// PDF engine to use. Defaults to pdflatex if installed, otherwise the built-in native engine.
+    #[clap(long, value_enum)]
+    pub pdf_engine: Option<PdfEngine>,
+
+    /// Custom LaTeX design file for PDF output (pdflatex engine only).
+    /// Booklet and card layouts require a sheet template with the {{QRLAN_PAGES}} placeholder.
     #[clap(long)]
     pub design: Option<String>,
+
+    /// Decode every generated PNG/JPG file and fail if it does not scan back to the intended network.
+    #[clap(long)]
+    pub verify: bool,
+
+    /// How the networks are arranged.
+    #[clap(long, value_enum, default_value_t = BatchLayout::Files)]
+    pub layout: BatchLayout,
+
+    /// Paper size of booklets and card sheets.
+    #[clap(long, value_enum, default_value_t = Paper::A4)]
+    pub paper: Paper,
+
+    /// Cards per sheet as COLUMNSxROWS (card layout only).
+    #[clap(long, value_parser = parse_grid, default_value = "2x4")]
+    pub grid: (usize, usize),
+}
+
+// Parses a card grid such as "2x4" into (columns, rows).
+fn parse_grid(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid grid '{}'. Use COLUMNSxROWS, e.g. 2x4.", value);
+    let (columns, rows) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    match (columns.trim().parse::<usize>(), rows.trim().parse::<usize>()) {
+        (Ok(columns), Ok(rows)) if columns > 0 && rows > 0 => Ok((columns, rows)),
+        _ => Err(invalid()),
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DecodeArgs {
+    /// Image file (.png or .jpg) containing one or more Wi-Fi QR codes.
+    pub image: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConnectArgs {
+    /// Image file (.png or .jpg) containing a Wi-Fi QR code, e.g. a photo of a printed card.
+    pub image: PathBuf,
+
+    /// Print the nmcli command instead of running it.
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+impl Args {
+    /// Returns the file format selected by the output format flags (PDF if none is given).
+    pub fn output_format(&self) -> OutputFormat {
+        if self.png {
+            OutputFormat::Png
+        } else if self.jpg {
+            OutputFormat::Jpg
+        } else if self.svg {
+            OutputFormat::Svg
+        } else {
+            OutputFormat::Pdf
+        }
+    }
+}
+
+/// File format of a generated QR code.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Pdf,
+    Png,
+    Jpg,
+    Svg,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [OutputFormat::Pdf, OutputFormat::Png, OutputFormat::Jpg, OutputFormat::Svg];
+
+    /// Returns the file extension (without the dot).
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Png => "png",
+            OutputFormat::Jpg => "jpg",
+            OutputFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Arrangement of the networks of a batch.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchLayout {
+    /// One file per network.
+    Files,
+    /// One PDF with one network per page.
+    Booklet,
+    /// One PDF with a grid of cards per page, separated by cut lines.
+    Cards,
+}
+
+/// Paper size of multi-network PDFs.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+/// Renderer used for PDF output.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PdfEngine {
+    /// Built-in renderer, no LaTeX installation required.
+    Native,
+    /// Compile the LaTeX template with pdflatex (supports custom designs).
+    Pdflatex,
 }
@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use crate::cli::ConnectArgs;
+
+// Quotes an argument for display in a POSIX shell command line.
+#[cfg(target_os = "linux")]
+fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_.,:/=@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Decodes the Wi-Fi QR code of an image and creates a NetworkManager connection for it,
+/// or prints the equivalent nmcli command with --dry-run.
+#[cfg(target_os = "linux")]
+pub fn run(args: &ConnectArgs) -> Result<(), Box<dyn Error>> {
+    use crate::{decode, qr_generator, wifi_utils};
+
+    // Use the first QR code that contains a Wi-Fi configuration.
+    let contents = decode::decode_image(&args.image)?;
+    let network = contents
+        .iter()
+        .find_map(|content| qr_generator::parse_qr_code_data(content).ok())
+        .ok_or(format!("No Wi-Fi QR code found in '{}'.", args.image.display()))?;
+
+    if args.dry_run {
+        let command: Vec<String> = wifi_utils::connection_add_args(&network)?.iter().map(|arg| shell_quote(arg)).collect();
+        println!("nmcli {}", command.join(" "));
+        return Ok(());
+    }
+
+    wifi_utils::add_connection(&network)?;
+    println!("Created NetworkManager connection '{}'.", network.ssid);
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn run(_args: &ConnectArgs) -> Result<(), Box<dyn Error>> {
+    Err("Connecting to a network from a QR code is only supported on Linux (NetworkManager).".into())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::qr_generator;
+    use crate::wifi_utils;
+
+    #[test]
+    fn shell_quote_quotes_only_unsafe_arguments() {
+        assert_eq!(shell_quote("wifi-sec.key-mgmt"), "wifi-sec.key-mgmt");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("Office Net"), "'Office Net'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+    }
+
+    #[test]
+    fn dry_run_command_of_decoded_network() {
+        let network = qr_generator::parse_qr_code_data(r"WIFI:S:Joe's Café;T:WPA;P:pa\;ss word;H:true;;").unwrap();
+        let command: Vec<String> = wifi_utils::connection_add_args(&network).unwrap().iter().map(|arg| shell_quote(arg)).collect();
+        assert_eq!(
+            command.join(" "),
+            r"connection add type wifi con-name 'Joe'\''s Café' ssid 'Joe'\''s Café' 802-11-wireless.hidden yes wifi-sec.key-mgmt wpa-psk wifi-sec.psk 'pa;ss word'"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::path::Path;
+
+use crate::cli::DecodeArgs;
+use crate::qr_generator;
+use crate::wifi_utils::WifiNetwork;
+
+/// Reads a PNG or JPG image and returns the contents of all QR codes found in it.
+///
+/// # Errors
+/// Returns an error if the image cannot be read or contains no readable QR code.
+pub fn decode_image(path: &Path) -> Result<Vec<String>, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to read image '{}': {}", path.display(), e))?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(image.width() as usize, image.height() as usize, |x, y| {
+        image.get_pixel(x as u32, y as u32)[0]
+    });
+
+    let grids = prepared.detect_grids();
+    if grids.is_empty() {
+        return Err(format!("No QR code found in '{}'.", path.display()));
+    }
+    grids
+        .iter()
+        .map(|grid| {
+            grid.decode()
+                .map(|(_, content)| content)
+                .map_err(|e| format!("Failed to decode QR code in '{}': {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// Decodes the generated image and checks that it contains exactly the expected payload.
+pub fn verify_image(path: &Path, expected_qr_data: &str) -> Result<(), String> {
+    let contents = decode_image(path)?;
+    if contents.iter().any(|content| content == expected_qr_data) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Verification failed: '{}' does not scan back to the intended network (decoded '{}').",
+            path.display(),
+            contents.join("', '")
+        ))
+    }
+}
+
+// Prints the details of a decoded network, one per line.
+fn print_network(network: &WifiNetwork) {
+    println!("SSID: {}", network.ssid);
+    if let Some(security_type) = network.security_type {
+        println!("Security: {}", security_type);
+    }
+    if let Some(ref password) = network.password {
+        println!("Password: {}", password);
+    }
+    println!("Hidden: {}", if network.hidden { "yes" } else { "no" });
+    if let Some(ref credentials) = network.enterprise {
+        println!("EAP method: {}", credentials.eap_method.qr_value());
+        if let Some(phase2) = credentials.phase2_method {
+            println!("Phase 2: {}", phase2.qr_value());
+        }
+        if let Some(ref identity) = credentials.identity {
+            println!("Identity: {}", identity);
+        }
+        if let Some(ref anonymous_identity) = credentials.anonymous_identity {
+            println!("Anonymous identity: {}", anonymous_identity);
+        }
+    }
+}
+
+/// Decodes the Wi-Fi QR codes of an image and prints the networks they contain.
+/// QR codes without a Wi-Fi configuration (e.g. a URL next to the Wi-Fi card) are reported and skipped.
+///
+/// # Errors
+/// Returns an error if the image contains no Wi-Fi QR code.
+pub fn run(args: &DecodeArgs) -> Result<(), Box<dyn Error>> {
+    let contents = decode_image(&args.image)?;
+    let mut printed = 0;
+    for content in &contents {
+        match qr_generator::parse_qr_code_data(content) {
+            Ok(network) => {
+                if printed > 0 {
+                    println!();
+                }
+                print_network(&network);
+                printed += 1;
+            }
+            Err(e) => eprintln!("Skipping QR code '{}': {}", content, e),
+        }
+    }
+    if printed == 0 {
+        return Err(format!("No Wi-Fi QR code found in '{}'.", args.image.display()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::SecurityType;
+    use image::{GenericImage, ImageBuffer, Luma};
+
+    // Renders a small QR code; the full-size images of create_qr_image are slow to scan in debug builds.
+    fn render(data: &str) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        qrcode::QrCode::new(data.as_bytes()).unwrap().render::<Luma<u8>>().max_dimensions(300, 300).build()
+    }
+
+    // Writes an image with a URL QR code next to a Wi-Fi QR code, like a photo of a printed card.
+    fn write_mixed_image(path: &Path, wifi_data: &str) {
+        let url = render("https://example.com/guest");
+        let wifi = render(wifi_data);
+        let mut image = ImageBuffer::from_pixel(url.width() + wifi.width(), url.height().max(wifi.height()), Luma([255u8]));
+        image.copy_from(&url, 0, 0).unwrap();
+        image.copy_from(&wifi, url.width(), 0).unwrap();
+        image.save(path).unwrap();
+    }
+
+    #[test]
+    fn run_skips_qr_codes_without_wifi_configuration() {
+        let wifi_data = qr_generator::generate_qr_code_data("Guest", "welcome;1", SecurityType::WpaPersonal, false, None);
+        let path = std::env::temp_dir().join(format!("qrlan_decode_test_{}.png", std::process::id()));
+        write_mixed_image(&path, &wifi_data);
+
+        let contents = decode_image(&path).unwrap();
+        let result = run(&DecodeArgs { image: path.clone() });
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(contents.len(), 2);
+        assert!(contents.contains(&wifi_data));
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,84 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+
+/// Exit code used when input is required but qrlan runs non-interactively.
+pub const EXIT_INPUT_REQUIRED: i32 = 2;
+
+/// Error returned when a value would have to be prompted for in non-interactive mode.
+#[derive(Debug)]
+pub struct InputRequiredError {
+    pub missing: String, // Description of the missing value, e.g. "the SSID".
+    pub hint: String,    // Flag(s) that provide the value, e.g. "--ssid".
+}
+
+impl fmt::Display for InputRequiredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Non-interactive mode requires {}. Provide it with {}.", self.missing, self.hint)
+    }
+}
+
+impl Error for InputRequiredError {}
+
+/// Reads answers from stdin, or fails with an `InputRequiredError` when running non-interactively.
+pub struct Input {
+    interactive: bool,
+}
+
+impl Input {
+    /// Creates the input handler. Non-interactive mode is forced by the flag or when stdin is not a TTY.
+    pub fn new(non_interactive_flag: bool) -> Input {
+        Input { interactive: !non_interactive_flag && io::stdin().is_terminal() }
+    }
+
+    /// Prints the message and reads one trimmed line from stdin.
+    /// In non-interactive mode, fails with an error naming the missing value and the flag(s) to provide it.
+    pub fn prompt(&self, message: &str, missing: &str, hint: &str) -> Result<String, Box<dyn Error>> {
+        if !self.interactive {
+            return Err(InputRequiredError { missing: missing.to_string(), hint: hint.to_string() }.into());
+        }
+        print!("{}", message);
+        io::stdout().flush()?;
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        Ok(line.trim().to_string())
+    }
+
+    /// Like `prompt`, but returns the default in non-interactive mode instead of failing.
+    pub fn prompt_or_default(&self, message: &str, default: &str) -> Result<String, Box<dyn Error>> {
+        if !self.interactive {
+            return Ok(default.to_string());
+        }
+        self.prompt(message, "", "")
+    }
+}
+
+// Removes the line break that usually terminates secrets stored in files or piped in.
+fn strip_line_ending(secret: &str) -> String {
+    secret.trim_end_matches(['\n', '\r']).to_string()
+}
+
+/// Reads a password from the first line of a file.
+pub fn read_password_file(path: &Path) -> Result<String, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read password file '{}': {}", path.display(), e))?;
+    Ok(strip_line_ending(content.lines().next().unwrap_or("")))
+}
+
+/// Reads a password from an environment variable.
+pub fn read_password_env(variable: &str) -> Result<String, String> {
+    std::env::var(variable)
+        .map(|value| strip_line_ending(&value))
+        .map_err(|e| format!("Failed to read password from environment variable '{}': {}", variable, e))
+}
+
+/// Reads a password from stdin (everything up to EOF, without the trailing line break).
+pub fn read_password_stdin() -> Result<String, String> {
+    let mut content = String::new();
+    io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read password from stdin: {}", e))?;
+    Ok(strip_line_ending(&content))
+}
@@ -1,108 +1,149 @@
+mod batch;
 mod cli;
+mod connect;
+mod decode;
+mod input;
+mod native_pdf;
+mod output;
 mod qr_generator;
+mod sheet;
 mod wifi_utils;
 mod update;
 
 use clap::Parser;
-use cli::Args;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::fs;
-use std::process::Command;
+use cli::{Args, Command, OutputFormat, PdfEngine};
+use input::{Input, InputRequiredError, EXIT_INPUT_REQUIRED};
+use wifi_utils::{FileSource, SecurityType, WifiNetwork, WifiSource};
+use std::error::Error;
+use std::path::{Path, PathBuf};
 use heck::ToSnakeCase;
-use dirs;
+
+// Reads the networks of a configuration file (see the importers in wifi_utils).
+type FileReader = fn(&Path) -> Result<Vec<WifiNetwork>, String>;
 
 // Helper function to prompt for manual SSID input
 // Returns Ok(Some(String)) if user enters an SSID, Ok(None) if user declines,
-// or an io::Error if reading input fails.
-fn prompt_for_manual_ssid() -> Result<Option<String>, io::Error> {
-    println!("Would you like to enter the SSID manually? (y/N)");
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice)?;
-    if choice.trim().eq_ignore_ascii_case("y") {
-        print!("Enter the SSID: ");
-        io::stdout().flush()?;
-        let mut ssid_manual = String::new();
-        io::stdin().read_line(&mut ssid_manual)?;
-        Ok(Some(ssid_manual.trim().to_string()))
+// or an error if reading input fails or qrlan runs non-interactively.
+fn prompt_for_manual_ssid(input: &Input) -> Result<Option<String>, Box<dyn Error>> {
+    let choice = input.prompt("Would you like to enter the SSID manually? (y/N)\n", "the SSID", "--ssid")?;
+    if choice.eq_ignore_ascii_case("y") {
+        Ok(Some(input.prompt("Enter the SSID: ", "the SSID", "--ssid")?))
     } else {
         Ok(None)
     }
 }
 
-fn check_pdflatex_availability() -> Result<(), String> {
-    match Command::new("pdflatex").arg("--version").output() {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(())
-            } else {
-                Err(
-                    "Error: 
-No LaTeX distribution was found. Ensure that the \"pdflatex\" command is available.
-
-For Windows use:
-MiKTeX (https://miktex.org/download)
-
-For macOS use:
-MacTeX (https://www.tug.org/mactex/mactex-download.html)
-
-For Linux (Debian/Ubuntu) use:
-sudo apt-get install texlive-latex-base texlive-fonts-recommended texlive-lang-english
+// Reads the password from the source given on the command line, if any.
+fn read_password_from_args(args: &Args) -> Result<Option<String>, String> {
+    if let Some(ref path) = args.password_file {
+        input::read_password_file(path).map(Some)
+    } else if let Some(ref variable) = args.password_env {
+        input::read_password_env(variable).map(Some)
+    } else if args.password_stdin {
+        input::read_password_stdin().map(Some)
+    } else {
+        Ok(None)
+    }
+}
 
-For Linux (Fedora) use:
-sudo dnf install texlive-scheme-basic texlive-collection-fontsrecommended texlive-collection-langenglish".to_string()
-                )
-            }
+// Returns the sources selected with --source and the --from-* flags. Empty if none were given.
+fn selected_sources(args: &Args) -> Result<Vec<Box<dyn WifiSource>>, String> {
+    let mut sources = args.source.iter().map(|name| wifi_utils::find_source(name)).collect::<Result<Vec<_>, _>>()?;
+    let files: [(&'static str, &Option<PathBuf>, FileReader); 7] = [
+        ("wpa_supplicant", &args.from_wpa_supplicant, wifi_utils::wpa_supplicant::read_config),
+        ("hostapd", &args.from_hostapd, wifi_utils::hostapd::read_config),
+        ("uci", &args.from_uci, wifi_utils::uci::read_config),
+        ("netplan", &args.from_netplan, wifi_utils::netplan::read_config),
+        ("windows-xml", &args.from_windows_xml, wifi_utils::windows_profile::read_profiles),
+        ("mobileconfig", &args.from_mobileconfig, wifi_utils::apple_profile::read_profile),
+        ("android", &args.from_android, wifi_utils::android::read_config_store),
+    ];
+    for (name, path, reader) in files {
+        if let Some(path) = path {
+            sources.push(Box::new(FileSource::new(name, path, reader)));
         }
-        Err(_) => {
-            Err(
-                "Error: 
-No LaTeX distribution was found. Ensure that the \"pdflatex\" command is available.
-
-For Windows use:
-MiKTeX (https://miktex.org/download)
+    }
+    Ok(sources)
+}
 
-For macOS use:
-MacTeX (https://www.tug.org/mactex/mactex-download.html)
+// The known networks along with the sources they were read from, used to look up passwords later.
+struct KnownNetworks {
+    sources: Vec<Box<dyn WifiSource>>,
+    networks: Result<Vec<WifiNetwork>, String>,
+}
 
-For Linux (Debian/Ubuntu) use:
-sudo apt-get install texlive-latex-base texlive-fonts-recommended texlive-lang-english
+// Reads the known networks. Selected sources must all be readable; otherwise the sources of this system
+// are probed, and a failure is kept in the network list so the SSID can still be entered manually.
+fn read_known_networks(args: &Args) -> Result<KnownNetworks, String> {
+    let sources = selected_sources(args)?;
+    if !sources.is_empty() {
+        let networks = wifi_utils::read_sources(&sources)?;
+        return Ok(KnownNetworks { sources, networks: Ok(networks) });
+    }
+    Ok(match wifi_utils::probe(wifi_utils::registry()) {
+        Ok((source, networks)) => KnownNetworks { sources: vec![source], networks: Ok(networks) },
+        Err(e) => KnownNetworks { sources: Vec::new(), networks: Err(e) },
+    })
+}
 
-For Linux (Fedora) use:
-sudo dnf install texlive-scheme-basic texlive-collection-fontsrecommended texlive-collection-langenglish".to_string()
-            )
-        }
+// Returns the SSID of the connected network. Failures only matter with --current;
+// otherwise the SSID is just used to mark the network in the list.
+fn read_current_ssid(args: &Args, sources: &[Box<dyn WifiSource>]) -> Result<Option<String>, String> {
+    if args.ssid.is_some() {
+        return Ok(None);
+    }
+    match wifi_utils::current_ssid(sources) {
+        Ok(ssid) => Ok(ssid),
+        Err(e) if args.current => Err(format!("Failed to determine the connected network: {}", e.trim_end_matches('.'))),
+        Err(_) => Ok(None),
     }
 }
 
-fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse(); // Parse arguments. Version flag is handled by clap.
+// Selects the network to generate the QR code for.
+// Returns Ok(None) if the user declined to enter an SSID manually.
+fn select_network(
+    args: &Args,
+    input: &Input,
+    known_networks: Result<Vec<WifiNetwork>, String>,
+    current_ssid: Option<String>,
+) -> Result<Option<WifiNetwork>, Box<dyn Error>> {
+    let requested_ssid = if args.current {
+        Some(current_ssid.clone().ok_or("Not connected to a Wi-Fi network, or the connected network could not be determined.")?)
+    } else {
+        args.ssid.clone()
+    };
+
+    // An SSID given on the command line (or the connected network) skips the selection;
+    // details of a matching known network are reused.
+    if let Some(ref ssid) = requested_ssid {
+        let known_networks = known_networks.unwrap_or_else(|e| {
+            eprintln!("Error retrieving Wi-Fi networks: {}.", e);
+            Vec::new()
+        });
+        let network = known_networks
+            .into_iter()
+            .find(|network| &network.ssid == ssid)
+            .unwrap_or_else(|| WifiNetwork::from_ssid(ssid.clone()));
+        return Ok(Some(network));
+    }
 
     // Attempt to retrieve known Wi-Fi networks.
-    let networks = match wifi_utils::get_known_networks() {
+    let networks = match known_networks {
         Ok(net) if !net.is_empty() => net, // Networks found
         Ok(_) => { // No networks found, prompt for manual entry
             println!("No known Wi-Fi networks found.");
-            match prompt_for_manual_ssid()? {
-                Some(ssid) => vec![wifi_utils::WifiNetwork { 
-                    ssid, 
-                    password: None, // Password will be prompted later
-                    security_type: None, // Security type will be prompted later
-                }],
+            match prompt_for_manual_ssid(input)? {
+                Some(ssid) => vec![WifiNetwork::from_ssid(ssid)], // Password and security type will be prompted later
                 None => {
                     println!("Exiting application as no SSID was provided.");
-                    return Ok(());
+                    return Ok(None);
                 }
             }
         }
         Err(e) => { // Error retrieving networks, prompt for manual entry
             eprintln!("Error retrieving Wi-Fi networks: {}.", e);
-            match prompt_for_manual_ssid()? {
-                Some(ssid) => vec![wifi_utils::WifiNetwork { 
-                    ssid, 
-                    password: None,
-                    security_type: None,
-                }],
+            match prompt_for_manual_ssid(input)? {
+                Some(ssid) => vec![WifiNetwork::from_ssid(ssid)],
                 None => {
                     eprintln!("Exiting application due to error and no manual SSID entry.");
                     return Err(e.into()); // Propagate the original error
@@ -114,46 +155,84 @@ fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
     // If, after all attempts, no networks are available, exit.
     if networks.is_empty() {
         println!("No Wi-Fi networks available to process. Exiting.");
-        return Ok(());
+        return Ok(None);
     }
 
-    let selected_network: wifi_utils::WifiNetwork;
-
     // If only one network is available, select it automatically.
     if networks.len() == 1 {
-        selected_network = networks[0].clone();
-        println!("Automatically selected the only available network: {}", selected_network.ssid);
+        println!("Automatically selected the only available network: {}", networks[0].ssid);
+        return Ok(Some(networks[0].clone()));
+    }
+
+    // Multiple networks available, prompt user for selection.
+    // The connected network is listed first and selected if the input is left empty.
+    let mut networks = networks;
+    let current_index = current_ssid.and_then(|ssid| networks.iter().position(|network| network.ssid == ssid));
+    if let Some(index) = current_index {
+        let current = networks.remove(index);
+        networks.insert(0, current);
+    }
+    println!("Available Wi-Fi networks:");
+    for (i, network) in networks.iter().enumerate() {
+        let marker = if current_index.is_some() && i == 0 { " (connected)" } else { "" };
+        println!("[{}]\t{}{}", i, network.ssid, marker);
+    }
+    let message = if current_index.is_some() {
+        "\nPlease select a network by number to generate the QR code for (Enter for the connected network): "
     } else {
-        // Multiple networks available, prompt user for selection.
-        println!("Available Wi-Fi networks:");
-        for (i, network) in networks.iter().enumerate() {
-            println!("[{}]\t{}", i, network.ssid);
-        }
+        "\nPlease select a network by number to generate the QR code for: "
+    };
 
-        loop {
-            print!("\nPlease select a network by number to generate the QR code for: ");
-            io::stdout().flush()?;
-            let mut selection_input = String::new();
-            io::stdin().read_line(&mut selection_input)?;
-            match selection_input.trim().parse::<usize>() {
-                Ok(num) if num < networks.len() => {
-                    selected_network = networks[num].clone();
-                    break;
-                }
-                _ => {
-                    eprintln!("Invalid selection. Please enter a number between 0 and {}.", networks.len() - 1);
-                }
-            };
+    loop {
+        let selection_input = input.prompt(message, "the SSID when multiple networks are known", "--ssid or --current")?;
+        if selection_input.is_empty() && current_index.is_some() {
+            return Ok(Some(networks[0].clone()));
         }
+        match selection_input.parse::<usize>() {
+            Ok(num) if num < networks.len() => return Ok(Some(networks[num].clone())),
+            _ => {
+                eprintln!("Invalid selection. Please enter a number between 0 and {}.", networks.len() - 1);
+            }
+        };
     }
+}
+
+// Enterprise QR codes need EAP settings, which can only be detected from the system.
+fn missing_eap_settings_error(ssid: &str) -> Box<dyn Error> {
+    format!(
+        "Enterprise networks require EAP settings such as the EAP method, but none were detected for '{}'. \
+         Import the network from a source that stores them, e.g. NetworkManager or a profile file.",
+        ssid
+    ).into()
+}
+
+fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse(); // Parse arguments. Version flag is handled by clap.
+
+    match args.command {
+        Some(Command::Batch(ref batch_args)) => return batch::run(batch_args),
+        Some(Command::Decode(ref decode_args)) => return decode::run(decode_args),
+        Some(Command::Connect(ref connect_args)) => return connect::run(connect_args),
+        None => {}
+    }
+
+    let input = Input::new(args.non_interactive);
+
+    let known_networks = read_known_networks(&args)?;
+    let current_ssid = read_current_ssid(&args, &known_networks.sources)?;
+    let selected_network = match select_network(&args, &input, known_networks.networks, current_ssid)? {
+        Some(network) => network,
+        None => return Ok(()),
+    };
     
     println!("Selected network: {}", selected_network.ssid);
 
-    // Attempt to fetch password if not already available from the network struct.
-    let mut final_password_candidate = selected_network.password.clone();
+    // A password given on the command line takes precedence over stored passwords.
+    // Otherwise, attempt to fetch the password if not already available from the network struct.
+    let mut final_password_candidate = read_password_from_args(&args)?.or_else(|| selected_network.password.clone());
 
     if final_password_candidate.is_none() {
-        match crate::wifi_utils::fetch_password_for_ssid(&selected_network.ssid) {
+        match wifi_utils::fetch_password(&known_networks.sources, &selected_network.ssid) {
             Ok(Some(fetched_pw)) => {
                 final_password_candidate = Some(fetched_pw);
             }
@@ -169,61 +248,83 @@ fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
     // Prompt for password if it's still not available.
     let password = if let Some(p) = final_password_candidate {
         p // Use existing or fetched password
+    } else if args.security.or(selected_network.security_type).is_some_and(|security| !security.requires_password()) {
+        String::new() // Open network given on the command line or detected, no password needed.
     } else {
-        print!("Enter the password for '{}' (leave empty for an open network): ", selected_network.ssid);
-        io::stdout().flush()?;
-        let mut pw_input = String::new();
-        io::stdin().read_line(&mut pw_input)?;
-        pw_input.trim().to_string()
+        input.prompt(
+            &format!("Enter the password for '{}' (leave empty for an open network): ", selected_network.ssid),
+            "a password",
+            "--password-file, --password-env, --password-stdin or '--security nopass'",
+        )?
     };
 
-    // Determine security type.
-    let final_security_type: String; // Will store the determined security type as a String
+    let mut enterprise = selected_network.enterprise.clone();
 
-    if let Some(st_from_detection) = &selected_network.security_type {
+    // Determine security type.
+    let final_security_type: SecurityType;
+
+    if args.security == Some(SecurityType::Enterprise) && enterprise.is_none() {
+        return Err(missing_eap_settings_error(&selected_network.ssid));
+    } else if let Some(st_from_args) = args.security {
+        // Security type was given on the command line
+        final_security_type = st_from_args;
+    } else if let Some(st_from_detection) = selected_network.security_type {
         // Security type was successfully detected by the OS-specific module
         println!("Automatically detected security type for '{}': {}", selected_network.ssid, st_from_detection);
-        final_security_type = st_from_detection.clone(); // Use the detected type
+        final_security_type = st_from_detection; // Use the detected type
     } else {
         // Security type was NOT detected (i.e., selected_network.security_type is None)
         println!("Could not automatically determine the security type for '{}'.", selected_network.ssid);
         if password.is_empty() {
             println!("No password was entered; assuming an open network ('nopass').");
-            final_security_type = "nopass".to_string();
+            final_security_type = SecurityType::Open;
         } else {
-            // Prompt the user for manual input
-            print!("Please enter the security type (e.g., WPA, WEP, or nopass if open; defaults to WPA): ");
-            io::stdout().flush()?;
-            let mut sec_type_input_str = String::new();
-            io::stdin().read_line(&mut sec_type_input_str)?;
-            let normalized_input = sec_type_input_str.trim().to_uppercase();
-
-            if normalized_input.is_empty() {
-                final_security_type = "WPA".to_string(); // Default to WPA
-            } else if normalized_input == "WEP" {
-                final_security_type = "WEP".to_string();
-            } else if normalized_input == "NOPASS" {
-                final_security_type = "nopass".to_string();
-            } else if normalized_input == "WPA" { // Handles WPA, WPA2, WPA3 under the WPA category for QR code
-                final_security_type = "WPA".to_string();
+            // Prompt the user for manual input (non-interactive mode uses the WPA default)
+            let sec_type_input_str = input.prompt_or_default(
+                "Please enter the security type (e.g., WPA, WPA3, WEP, or nopass if open; defaults to WPA): ",
+                "",
+            )?;
+            let trimmed_input = sec_type_input_str.as_str();
+
+            if trimmed_input.is_empty() {
+                final_security_type = SecurityType::WpaPersonal; // Default to WPA
             } else {
-                println!("Invalid security type entered. Defaulting to WPA.");
-                final_security_type = "WPA".to_string();
+                match trimmed_input.parse::<SecurityType>() {
+                    // The EAP settings were not detected, otherwise the type would be known.
+                    Ok(SecurityType::Enterprise) => return Err(missing_eap_settings_error(&selected_network.ssid)),
+                    Ok(parsed) => final_security_type = parsed,
+                    Err(_) => {
+                        println!("Invalid security type entered. Defaulting to WPA.");
+                        final_security_type = SecurityType::WpaPersonal;
+                    }
+                }
+            }
+        }
+    }
+
+    // The EAP settings only belong in the QR code of enterprise networks, e.g. not with '--security wpa'.
+    if final_security_type != SecurityType::Enterprise {
+        enterprise = None;
+    }
+    // Enterprise networks need an identity (username) to authenticate; prompt if it was not stored.
+    if let Some(credentials) = enterprise.as_mut() {
+        if credentials.identity.is_none() {
+            let prompt = format!("Enter the identity (username) for '{}': ", selected_network.ssid);
+            let identity = input.prompt_or_default(&prompt, "")?;
+            if !identity.is_empty() {
+                credentials.identity = Some(identity);
             }
         }
     }
 
-    let mut title_str = String::new();
-    let mut prompted_filename_str = String::new();
+    let mut title_str = args.title.clone().unwrap_or_default();
+    let mut prompted_filename_str = args.filename.clone().unwrap_or_default();
 
     if !args.show {
         // Prompt for an optional title for the PDF if no image format is specified.
-        if !args.png && !args.jpg && !args.svg {
-            print!("Enter a title for the PDF (optional, press Enter to use SSID '{}'): ", selected_network.ssid);
-            io::stdout().flush()?;
-            let mut title_input = String::new();
-            io::stdin().read_line(&mut title_input)?;
-            title_str = title_input.trim().to_string();
+        if !args.png && !args.jpg && !args.svg && args.title.is_none() {
+            let prompt = format!("Enter a title for the PDF (optional, press Enter to use SSID '{}'): ", selected_network.ssid);
+            title_str = input.prompt_or_default(&prompt, "")?;
         }
 
         // Determine the appropriate extension based on arguments.
@@ -237,57 +338,22 @@ fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         // Prompt for an optional filename.
-        print!("Enter a filename (optional, press Enter to use '{}_qrcode.{}'): ", selected_network.ssid.to_snake_case(), suggested_extension);
-        io::stdout().flush()?;
-        let mut filename_input = String::new();
-        io::stdin().read_line(&mut filename_input)?;
-        prompted_filename_str = filename_input.trim().to_string();
-    }
-
-    // Determine the base name for the output PDF file.
-    // Uses prompted filename, or defaults to SSID (snake_case) + "_qrcode".
-    let base_name_for_file = if !prompted_filename_str.is_empty() {
-        // Remove extension if present, as it will be added later.
-        if prompted_filename_str.to_lowercase().ends_with(".pdf") || prompted_filename_str.to_lowercase().ends_with(".png") || prompted_filename_str.to_lowercase().ends_with(".jpg") || prompted_filename_str.to_lowercase().ends_with(".svg") {
-            let extension_length = prompted_filename_str.split('.').last().unwrap_or("").len();
-            prompted_filename_str[..prompted_filename_str.len()-extension_length-1].to_string()
-        } else {
-            prompted_filename_str.clone()
+        if args.filename.is_none() {
+            let prompt = format!("Enter a filename (optional, press Enter to use '{}_qrcode.{}'): ", selected_network.ssid.to_snake_case(), suggested_extension);
+            prompted_filename_str = input.prompt_or_default(&prompt, "")?;
         }
-    } else {
-        selected_network.ssid.to_snake_case() + "_qrcode"
-    };
-
-    let final_path: PathBuf;
-
-    // Determine the final output path for the PDF.
-    // Uses path from CLI arguments if provided, otherwise defaults to Desktop.
-    if let Some(ref cli_path_str) = args.output_path {
-        let cli_p = PathBuf::from(cli_path_str);
-        // If the provided path is a directory, append the base filename.
-        if cli_p.is_dir() || cli_p.to_string_lossy().ends_with('/') || cli_p.to_string_lossy().ends_with('\\') {
-            fs::create_dir_all(&cli_p)?; // Ensure directory exists
-            final_path = cli_p.join(format!("{}.pdf", base_name_for_file));
-        } else {
-            // If it's a file path, ensure parent directory exists.
-            if let Some(parent) = cli_p.parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent)?;
-                }
-            }
-            final_path = cli_p.with_extension("pdf"); // Ensure .pdf extension
-        }
-    } else {
-        // Default to user's desktop directory.
-        let desktop_dir = dirs::desktop_dir().ok_or("Could not find the desktop directory.")?;
-        if !desktop_dir.exists(){
-            fs::create_dir_all(&desktop_dir)?;
-        }
-        final_path = desktop_dir.join(format!("{}.pdf", base_name_for_file));
     }
 
     // Generate QR code data string.
-    let qr_data = qr_generator::generate_qr_code_data(&selected_network.ssid, &password, &final_security_type);
+    // The --hidden flag forces the hidden field, e.g. for networks entered manually.
+    let hidden = args.hidden || selected_network.hidden;
+    let qr_data = qr_generator::generate_qr_code_data(
+        &selected_network.ssid,
+        &password,
+        final_security_type,
+        hidden,
+        enterprise.as_ref(),
+    );
     
     // Create QR code image.
     // This image is needed for PDF, PNG, JPG. SVG and show do not need it here.
@@ -333,97 +399,49 @@ fn actual_main() -> Result<(), Box<dyn std::error::Error>> {
             // If the SSID is wider than or equal to the QR code, output it left-aligned
             println!("{}", ssid);
         }
-    } else if args.png || args.jpg || args.svg {
-        // Logic for image generation (PNG, JPG, SVG)
-        let extension = if args.png { "png" } else if args.jpg { "jpg" } else { "svg" };
-        let base_name_for_file = if !prompted_filename_str.is_empty() {
-            if prompted_filename_str.to_lowercase().ends_with(extension) {
-                prompted_filename_str[..prompted_filename_str.len() - (extension.len() + 1)].to_string()
-            } else {
-                prompted_filename_str.clone()
+    } else {
+        let format = args.output_format();
+
+        // The PDF engine is resolved before writing, so a missing LaTeX installation fails early.
+        let pdf_engine = if format == OutputFormat::Pdf {
+            match output::resolve_pdf_engine(args.pdf_engine, args.design.is_some()) {
+                Ok(engine) => engine,
+                Err(err_msg) => {
+                    eprintln!("{}", err_msg);
+                    std::process::exit(1);
+                }
             }
         } else {
-            selected_network.ssid.to_snake_case() + "_qrcode"
+            PdfEngine::Native // Not used for image formats.
         };
 
-        let final_image_path: PathBuf;
-        if let Some(ref cli_path_str) = args.output_path {
-            let cli_p = PathBuf::from(cli_path_str);
-            if cli_p.is_dir() || cli_p.to_string_lossy().ends_with('/') || cli_p.to_string_lossy().ends_with('\\') {
-                fs::create_dir_all(&cli_p)?;
-                final_image_path = cli_p.join(format!("{}.{}", base_name_for_file, extension));
-            } else {
-                if let Some(parent) = cli_p.parent() {
-                    if !parent.exists() {
-                        fs::create_dir_all(parent)?;
-                    }
-                }
-                final_image_path = cli_p.with_extension(extension);
-            }
+        // Uses prompted filename, or defaults to SSID (snake_case) + "_qrcode".
+        let base_name_for_file = if !prompted_filename_str.is_empty() {
+            output::strip_output_extension(&prompted_filename_str)
         } else {
-            // Save to desktop by default if no path is specified
-            let desktop_dir = dirs::desktop_dir().ok_or("Could not find the desktop directory.")?;
-            if !desktop_dir.exists(){
-                fs::create_dir_all(&desktop_dir)?;
-            }
-            final_image_path = desktop_dir.join(format!("{}.{}", base_name_for_file, extension));
-            println!("No output path specified, saving to desktop: {}", final_image_path.display());
-        }
+            output::default_base_name(&selected_network.ssid)
+        };
+        let final_path = output::resolve_output_path(args.output_path.as_deref(), &base_name_for_file, format)?;
 
-        if args.svg {
-            match qr_generator::save_qr_as_svg(&qr_data, &final_image_path) {
-                Ok(_) => println!("Successfully generated QR code SVG: {}", final_image_path.display()),
-                Err(e) => eprintln!("Error saving QR code SVG: {}.", e),
-            }
+        let pdf_title_to_use = if title_str.is_empty() {
+            output::default_title(&selected_network.ssid, pdf_engine)
         } else {
-            // PNG or JPG
-            match qr_generator::create_qr_image(&qr_data) {
-                Some(qr_image) => {
-                    if args.png {
-                        match qr_generator::save_qr_as_png(&qr_image, &final_image_path) {
-                            Ok(_) => println!("Successfully generated QR code PNG: {}", final_image_path.display()),
-                            Err(e) => eprintln!("Error saving QR code PNG: {}.", e),
-                        }
-                    } else if args.jpg {
-                        match qr_generator::save_qr_as_jpg(&qr_image, &final_image_path) {
-                            Ok(_) => println!("Successfully generated QR code JPG: {}", final_image_path.display()),
-                            Err(e) => eprintln!("Error saving QR code JPG: {}.", e),
-                        }
-                    }
-                }
-                None => {
-                    eprintln!("Error creating QR code image for PNG/JPG.");
-                    return Err("QR code image creation failed".into());
-                }
-            }
-        }
-    } else {
-        // Default to PDF generation
-        if let Err(err_msg) = check_pdflatex_availability() {
-            eprintln!("{}", err_msg);
-            std::process::exit(1);
-        }
+            title_str
+        };
 
-        match qr_generator::create_qr_image(&qr_data) {
-            Some(qr_image) => {
-                let pdf_title_to_use = if title_str.is_empty() {
-                    &selected_network.ssid
-                } else {
-                    &title_str
-                };
-
-                match qr_generator::save_qr_as_pdf(&qr_image, &final_path, pdf_title_to_use, args.design.as_ref()) {
-                    Ok(_) => println!(
-                        "Successfully generated QR code PDF: {}",
-                        final_path.display()
-                    ),
-                    Err(e) => eprintln!("Error saving QR code PDF: {}.", e),
+        let format_name = format.extension().to_uppercase();
+        match output::write_qr_code(&qr_data, format, &final_path, &pdf_title_to_use, pdf_engine, args.design.as_ref()) {
+            Ok(_) => {
+                println!("Successfully generated QR code {}: {}", format_name, final_path.display());
+                if args.verify {
+                    if output::verify_qr_code(&qr_data, format, &final_path)? {
+                        println!("Verified: the QR code scans back to the intended network.");
+                    } else {
+                        println!("Note: --verify only checks PNG and JPG files, the {} was not verified.", format_name);
+                    }
                 }
             }
-            None => {
-                eprintln!("Error creating QR code image for PDF.");
-                return Err("QR code image creation failed".into());
-            }
+            Err(e) => return Err(format!("Error saving QR code {}: {}.", format_name, e).into()),
         }
     }
 
@@ -439,6 +457,8 @@ fn main_with_update_check() -> Result<(), Box<dyn std::error::Error>> {
 pub fn main() {
     if let Err(e) = main_with_update_check() {
         eprintln!("Application error: {}", e);
-        std::process::exit(1);
+        // Missing input in non-interactive mode gets a distinct exit code for scripts.
+        let exit_code = if e.is::<InputRequiredError>() { EXIT_INPUT_REQUIRED } else { 1 };
+        std::process::exit(exit_code);
     }
 }
\ No newline at end of file
@@ -0,0 +1,295 @@
+use printpdf::{
+    BuiltinFont, Color, CustomPdfConformance, Greyscale, IndirectFontRef, Line, LineDashPattern, Mm,
+    PdfConformance, PdfDocument, PdfDocumentReference, PdfLayerReference, Point,
+};
+use printpdf::indices::{PdfLayerIndex, PdfPageIndex};
+use qrcode::{Color as ModuleColor, QrCode};
+use std::fs;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::cli::Paper;
+use crate::sheet::{SheetEntry, SheetLayout};
+
+const QUIET_ZONE_MODULES: usize = 4; // Same quiet zone as the raster images.
+const PT_PER_MM: f64 = 72.0 / 25.4;
+const CUT_LINE_DASH_PT: i64 = 3; // Length of the dashes (and gaps) of the cut lines.
+
+// Glyph widths of Helvetica-Bold for the printable ASCII range (32..=126), in 1/1000 em.
+// Taken from the Adobe font metrics; the base-14 fonts are not embedded, so there is no font file to measure.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, // ' ' to '/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, // '0' to '?'
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, // '@' to 'O'
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556, // 'P' to '_'
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611, // '`' to 'o'
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,      // 'p' to '~'
+];
+const DEFAULT_GLYPH_WIDTH: u16 = 611; // Approximation for characters outside the ASCII range.
+
+// Characters of Windows-1252 in the range 0x80..=0x9F; the rest of the encoding matches Latin-1.
+const WIN_ANSI_EXTRA_CHARACTERS: &str = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+
+// Returns true if the character can be drawn with the built-in fonts, which use WinAnsiEncoding.
+// printpdf drops all other characters from the text without notice.
+fn is_win_ansi(c: char) -> bool {
+    matches!(c as u32, 32..=126 | 160..=255) || WIN_ANSI_EXTRA_CHARACTERS.contains(c)
+}
+
+// Fails if the title contains characters the built-in font cannot draw, instead of printing a truncated title.
+fn check_title_characters(title: &str) -> Result<(), String> {
+    let unsupported: String = title.chars().filter(|&c| !is_win_ansi(c)).collect();
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The title '{}' contains characters the native PDF engine cannot display ({}). \
+             Use a different title, '--pdf-engine pdflatex' or an image format",
+            title, unsupported
+        ))
+    }
+}
+
+/// Returns the SSID as a default title the built-in font can display.
+/// Characters outside of WinAnsiEncoding are left out; a generic title is used if none remain.
+pub fn default_title(ssid: &str) -> String {
+    let displayable: String = ssid.chars().filter(|&c| is_win_ansi(c)).collect();
+    let title = displayable.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        "Wi-Fi".to_string()
+    } else {
+        title
+    }
+}
+
+// Returns the width of the text set in Helvetica-Bold at the given size, in millimeters.
+fn text_width_mm(text: &str, font_size_pt: f64) -> f64 {
+    let width_units: u32 = text
+        .chars()
+        .map(|c| match c as u32 {
+            code @ 32..=126 => HELVETICA_BOLD_WIDTHS[(code - 32) as usize],
+            _ => DEFAULT_GLYPH_WIDTH,
+        } as u32)
+        .sum();
+    width_units as f64 / 1000.0 * font_size_pt / PT_PER_MM
+}
+
+// Builds a filled rectangle from its lower left corner and size.
+fn filled_rectangle(x: f64, y: f64, width: f64, height: f64) -> Line {
+    Line {
+        points: vec![
+            (Point::new(Mm(x), Mm(y)), false),
+            (Point::new(Mm(x + width), Mm(y)), false),
+            (Point::new(Mm(x + width), Mm(y + height)), false),
+            (Point::new(Mm(x), Mm(y + height)), false),
+        ],
+        is_closed: true,
+        has_fill: true,
+        has_stroke: false,
+        is_clipping_path: false,
+    }
+}
+
+/// Draws the QR code as vector rectangles with its lower left corner (including the quiet zone) at (x, y).
+fn draw_qr_code(layer: &PdfLayerReference, code: &QrCode, x: f64, y: f64, size: f64) {
+    let modules_per_side = code.width();
+    let module_size = size / (modules_per_side + 2 * QUIET_ZONE_MODULES) as f64;
+    let colors = code.to_colors();
+
+    layer.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+    for row in 0..modules_per_side {
+        // Merge horizontal runs of dark modules into one rectangle to keep the file small.
+        let mut column = 0;
+        while column < modules_per_side {
+            if colors[row * modules_per_side + column] != ModuleColor::Dark {
+                column += 1;
+                continue;
+            }
+            let run_start = column;
+            while column < modules_per_side && colors[row * modules_per_side + column] == ModuleColor::Dark {
+                column += 1;
+            }
+            let module_x = x + (QUIET_ZONE_MODULES + run_start) as f64 * module_size;
+            // PDF coordinates start at the bottom, QR rows at the top.
+            let module_y = y + size - (QUIET_ZONE_MODULES + row + 1) as f64 * module_size;
+            layer.add_shape(filled_rectangle(module_x, module_y, (column - run_start) as f64 * module_size, module_size));
+        }
+    }
+}
+
+/// Writes the title horizontally centered with its baseline at y, scaled down if wider than max_width.
+fn draw_centered_title(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    title: &str,
+    center_x: f64,
+    y: f64,
+    font_size_pt: f64,
+    max_width: f64,
+) {
+    let natural_width = text_width_mm(title, font_size_pt);
+    let font_size = if natural_width > max_width { font_size_pt * max_width / natural_width } else { font_size_pt };
+    let width = text_width_mm(title, font_size);
+    layer.use_text(title, font_size, Mm(center_x - width / 2.0), Mm(y), font);
+}
+
+/// Draws the card outline as a dashed cut line with its lower left corner at (x, y).
+fn draw_cut_lines(layer: &PdfLayerReference, x: f64, y: f64, width: f64, height: f64) {
+    let mut dash_pattern = LineDashPattern::default();
+    dash_pattern.dash_1 = Some(CUT_LINE_DASH_PT);
+    layer.set_line_dash_pattern(dash_pattern);
+    layer.set_outline_color(Color::Greyscale(Greyscale::new(0.6, None)));
+    layer.set_outline_thickness(0.5);
+    layer.add_shape(Line {
+        points: vec![
+            (Point::new(Mm(x), Mm(y)), false),
+            (Point::new(Mm(x + width), Mm(y)), false),
+            (Point::new(Mm(x + width), Mm(y + height)), false),
+            (Point::new(Mm(x), Mm(y + height)), false),
+        ],
+        is_closed: true,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false,
+    });
+    layer.set_line_dash_pattern(LineDashPattern::default());
+}
+
+/// Draws one card: the title above the QR code, vertically centered on the card as \vspace*{\fill} does in the templates.
+fn draw_card(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    layout: &SheetLayout,
+    entry: &SheetEntry,
+    index_on_page: usize,
+) -> Result<(), String> {
+    let code = QrCode::new(entry.qr_data.as_bytes())
+        .map_err(|e| format!("Failed to generate QR code for '{}': {}", entry.title, e))?;
+    let (x, y) = layout.card_origin(index_on_page);
+    let (card_width, card_height) = layout.card_size();
+    if layout.cut_marks {
+        draw_cut_lines(layer, x, y, card_width, card_height);
+    }
+
+    let qr_size = layout.qr_size();
+    let title_height = layout.title_height();
+    let block_height = title_height + layout.title_gap() + qr_size;
+    let block_top = y + (card_height + block_height) / 2.0;
+    let qr_y = block_top - block_height;
+
+    // The baseline sits below the top of the line by roughly the font's ascent.
+    let title_baseline = block_top - title_height * 0.72;
+    let center_x = x + card_width / 2.0;
+    let max_title_width = card_width - 2.0 * layout.padding();
+    draw_centered_title(layer, font, &entry.title, center_x, title_baseline, layout.title_font_size(), max_title_width);
+    draw_qr_code(layer, &code, center_x - qr_size / 2.0, qr_y, qr_size);
+    Ok(())
+}
+
+/// Creates an empty PDF document without the ICC profile and XMP metadata required by PDF/X.
+fn new_document(title: &str, page_width: f64, page_height: f64) -> (PdfDocumentReference, PdfPageIndex, PdfLayerIndex) {
+    let (document, page, layer) = PdfDocument::new(title, Mm(page_width), Mm(page_height), "QR code");
+    let document = document.with_conformance(PdfConformance::Custom(CustomPdfConformance {
+        requires_icc_profile: false,
+        requires_xmp_metadata: false,
+        ..Default::default()
+    }));
+    (document, page, layer)
+}
+
+/// Saves the document to the given path.
+fn write_document(document: PdfDocumentReference, output_pdf_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(output_dir) = output_pdf_path.parent() {
+        if !output_dir.as_os_str().is_empty() && !output_dir.exists() {
+            fs::create_dir_all(output_dir)
+                .map_err(|e| format!("Failed to create output directory '{:?}': {}", output_dir, e))?;
+        }
+    }
+    let file = fs::File::create(output_pdf_path)
+        .map_err(|e| format!("Failed to create PDF file '{:?}': {}", output_pdf_path, e))?;
+    document
+        .save(&mut BufWriter::new(file))
+        .map_err(|e| format!("Failed to write PDF file '{:?}': {}", output_pdf_path, e))?;
+    Ok(())
+}
+
+/// Saves several QR codes into one PDF without LaTeX, laid out as a grid of cards per page.
+/// Booklet layouts reproduce the standard template on every page; card layouts add dashed cut lines.
+///
+/// # Arguments
+/// * `entries` - The networks to include, in order.
+/// * `layout` - Paper size and card grid.
+/// * `output_pdf_path` - Path where the PDF will be saved.
+///
+/// # Errors
+/// Returns an error if a title contains characters outside of WinAnsiEncoding (e.g. CJK or emoji),
+/// a QR code cannot be generated or the file cannot be written.
+pub fn save_sheet_as_pdf(
+    entries: &[SheetEntry],
+    layout: &SheetLayout,
+    output_pdf_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in entries {
+        check_title_characters(&entry.title)?;
+    }
+    let document_title = entries.first().map(|entry| entry.title.as_str()).unwrap_or("QR codes");
+    let (document, first_page, first_layer) = new_document(document_title, layout.paper_width, layout.paper_height);
+    let font = document
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| format!("Failed to load PDF font: {}", e))?;
+
+    for (page_index, page_entries) in entries.chunks(layout.cards_per_page()).enumerate() {
+        let (page, layer) = if page_index == 0 {
+            (first_page, first_layer)
+        } else {
+            document.add_page(Mm(layout.paper_width), Mm(layout.paper_height), "QR code")
+        };
+        let layer = document.get_page(page).get_layer(layer);
+        for (index_on_page, entry) in page_entries.iter().enumerate() {
+            draw_card(&layer, &font, layout, entry, index_on_page)?;
+        }
+    }
+
+    write_document(document, output_pdf_path)
+}
+
+/// Saves the QR code as a PDF without LaTeX, reproducing the layout of the standard template:
+/// a centered title above a QR code spanning 60% of the page width, vertically centered on an A4 page.
+///
+/// # Arguments
+/// * `data` - The WIFI payload to encode.
+/// * `output_pdf_path` - Path where the PDF will be saved.
+/// * `title` - Title to be displayed above the QR code.
+///
+/// # Errors
+/// Returns an error if the title cannot be displayed, the QR code cannot be generated or the file cannot be written.
+pub fn save_qr_as_pdf(data: &str, output_pdf_path: &Path, title: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let entry = SheetEntry { title: title.to_string(), qr_data: data.to_string() };
+    save_sheet_as_pdf(&[entry], &SheetLayout::booklet(Paper::A4), output_pdf_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_title_characters_accepts_win_ansi() {
+        assert!(check_title_characters("Office Wi-Fi").is_ok());
+        assert!(check_title_characters("Café Ümlaut – 5 €").is_ok());
+    }
+
+    #[test]
+    fn check_title_characters_rejects_other_characters() {
+        let error = check_title_characters("東京 Guest 📶").unwrap_err();
+        assert!(error.contains("(東京📶)"), "{}", error);
+        assert!(check_title_characters("Ωmega").is_err());
+    }
+
+    #[test]
+    fn default_title_leaves_out_other_characters() {
+        assert_eq!(default_title("Café Guest"), "Café Guest");
+        assert_eq!(default_title("東京 Guest 📶"), "Guest");
+        assert_eq!(default_title("東京"), "Wi-Fi");
+        assert!(check_title_characters(&default_title("Ωmega 5 €")).is_ok());
+    }
+}
@@ -0,0 +1,198 @@
+use heck::ToSnakeCase;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::{OutputFormat, PdfEngine};
+use crate::sheet::{SheetEntry, SheetLayout};
+use crate::{decode, native_pdf, qr_generator};
+
+pub fn check_pdflatex_availability() -> Result<(), String> {
+    match Command::new("pdflatex").arg("--version").output() {
+        Ok(output) => {
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(
+                    "Error: 
+No LaTeX distribution was found. Ensure that the \"pdflatex\" command is available.
+
+For Windows use:
+MiKTeX (https://miktex.org/download)
+
+For macOS use:
+MacTeX (https://www.tug.org/mactex/mactex-download.html)
+
+For Linux (Debian/Ubuntu) use:
+sudo apt-get install texlive-latex-base texlive-fonts-recommended texlive-lang-english
+
+For Linux (Fedora) use:
+sudo dnf install texlive-scheme-basic texlive-collection-fontsrecommended texlive-collection-langenglish".to_string()
+                )
+            }
+        }
+        Err(_) => {
+            Err(
+                "Error: 
+No LaTeX distribution was found. Ensure that the \"pdflatex\" command is available.
+
+For Windows use:
+MiKTeX (https://miktex.org/download)
+
+For macOS use:
+MacTeX (https://www.tug.org/mactex/mactex-download.html)
+
+For Linux (Debian/Ubuntu) use:
+sudo apt-get install texlive-latex-base texlive-fonts-recommended texlive-lang-english
+
+For Linux (Fedora) use:
+sudo dnf install texlive-scheme-basic texlive-collection-fontsrecommended texlive-collection-langenglish".to_string()
+            )
+        }
+    }
+}
+
+// Determines the PDF engine to use.
+// Without an explicit choice, pdflatex is used if installed, otherwise the native engine.
+// Custom LaTeX designs always require pdflatex.
+pub fn resolve_pdf_engine(requested: Option<PdfEngine>, has_custom_design: bool) -> Result<PdfEngine, String> {
+    match requested {
+        Some(PdfEngine::Native) => {
+            if has_custom_design {
+                eprintln!("Note: --design is ignored by the native PDF engine.");
+            }
+            Ok(PdfEngine::Native)
+        }
+        Some(PdfEngine::Pdflatex) => check_pdflatex_availability().map(|_| PdfEngine::Pdflatex),
+        None if has_custom_design => check_pdflatex_availability().map(|_| PdfEngine::Pdflatex),
+        None => match check_pdflatex_availability() {
+            Ok(()) => Ok(PdfEngine::Pdflatex),
+            Err(_) => {
+                println!("No LaTeX distribution found, using the native PDF engine.");
+                Ok(PdfEngine::Native)
+            }
+        },
+    }
+}
+
+/// Removes a known output extension (.pdf, .png, .jpg, .svg) from a filename, as it is added later.
+pub fn strip_output_extension(filename: &str) -> String {
+    let lowercase = filename.to_lowercase();
+    for format in OutputFormat::ALL {
+        if lowercase.ends_with(&format!(".{}", format.extension())) {
+            return filename[..filename.len() - format.extension().len() - 1].to_string();
+        }
+    }
+    filename.to_string()
+}
+
+/// Returns the default base filename for a network: SSID (snake_case) + "_qrcode".
+pub fn default_base_name(ssid: &str) -> String {
+    ssid.to_snake_case() + "_qrcode"
+}
+
+/// Returns the user's desktop directory, which is the default output location. Creates it if missing.
+pub fn default_output_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let desktop_dir = dirs::desktop_dir().ok_or("Could not find the desktop directory.")?;
+    if !desktop_dir.exists() {
+        fs::create_dir_all(&desktop_dir)?;
+    }
+    Ok(desktop_dir)
+}
+
+/// Determines the final output path for a generated file.
+/// Uses the path from the CLI arguments if provided (a directory or a file path), otherwise defaults to the Desktop.
+/// Missing directories are created.
+pub fn resolve_output_path(output_path: Option<&Path>, base_name: &str, format: OutputFormat) -> Result<PathBuf, Box<dyn Error>> {
+    let extension = format.extension();
+    if let Some(cli_p) = output_path {
+        // If the provided path is a directory, append the base filename.
+        if cli_p.is_dir() || cli_p.to_string_lossy().ends_with('/') || cli_p.to_string_lossy().ends_with('\\') {
+            fs::create_dir_all(cli_p)?; // Ensure directory exists
+            Ok(cli_p.join(format!("{}.{}", base_name, extension)))
+        } else {
+            // If it's a file path, ensure parent directory exists.
+            if let Some(parent) = cli_p.parent() {
+                if !parent.as_os_str().is_empty() && !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            Ok(cli_p.with_extension(extension)) // Ensure matching extension
+        }
+    } else {
+        // Default to user's desktop directory.
+        let final_path = default_output_dir()?.join(format!("{}.{}", base_name, extension));
+        println!("No output path specified, saving to desktop: {}", final_path.display());
+        Ok(final_path)
+    }
+}
+
+/// Returns the PDF title used when none is set: the SSID, limited to the characters the PDF engine can display.
+pub fn default_title(ssid: &str, pdf_engine: PdfEngine) -> String {
+    match pdf_engine {
+        PdfEngine::Native => native_pdf::default_title(ssid),
+        PdfEngine::Pdflatex => ssid.to_string(),
+    }
+}
+
+/// Renders the WIFI payload in the given format and saves it to the output path.
+///
+/// # Arguments
+/// * `qr_data` - The WIFI payload created by `generate_qr_code_data`.
+/// * `format` - The output file format.
+/// * `output_path` - Path where the file will be saved.
+/// * `title` - Title displayed above the QR code (PDF only).
+/// * `pdf_engine` - Renderer used for PDF output, see `resolve_pdf_engine`.
+/// * `design` - Optional custom LaTeX template (pdflatex engine only).
+pub fn write_qr_code(
+    qr_data: &str,
+    format: OutputFormat,
+    output_path: &Path,
+    title: &str,
+    pdf_engine: PdfEngine,
+    design: Option<&String>,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Svg => qr_generator::save_qr_as_svg(qr_data, output_path),
+        OutputFormat::Pdf if pdf_engine == PdfEngine::Native => native_pdf::save_qr_as_pdf(qr_data, output_path, title),
+        OutputFormat::Pdf | OutputFormat::Png | OutputFormat::Jpg => {
+            let qr_image = qr_generator::create_qr_image(qr_data).ok_or("QR code image creation failed")?;
+            match format {
+                OutputFormat::Png => qr_generator::save_qr_as_png(&qr_image, output_path),
+                OutputFormat::Jpg => qr_generator::save_qr_as_jpg(&qr_image, output_path),
+                _ => qr_generator::save_qr_as_pdf(&qr_image, output_path, title, design),
+            }
+        }
+    }
+}
+
+/// Decodes a generated file and checks that it scans back to the WIFI payload.
+/// Only raster formats (PNG, JPG) can be decoded; returns false for other formats, which are not checked.
+pub fn verify_qr_code(qr_data: &str, format: OutputFormat, output_path: &Path) -> Result<bool, String> {
+    match format {
+        OutputFormat::Png | OutputFormat::Jpg => decode::verify_image(output_path, qr_data).map(|_| true),
+        OutputFormat::Pdf | OutputFormat::Svg => Ok(false),
+    }
+}
+
+/// Renders several WIFI payloads into one PDF (booklet or card sheets) and saves it to the output path.
+///
+/// # Arguments
+/// * `entries` - The networks to include, in order.
+/// * `layout` - Paper size and card grid.
+/// * `output_path` - Path where the PDF will be saved.
+/// * `pdf_engine` - Renderer used for the PDF, see `resolve_pdf_engine`.
+/// * `design` - Optional custom LaTeX sheet template (pdflatex engine only).
+pub fn write_sheet(
+    entries: &[SheetEntry],
+    layout: &SheetLayout,
+    output_path: &Path,
+    pdf_engine: PdfEngine,
+    design: Option<&String>,
+) -> Result<(), Box<dyn Error>> {
+    match pdf_engine {
+        PdfEngine::Native => native_pdf::save_sheet_as_pdf(entries, layout, output_path),
+        PdfEngine::Pdflatex => qr_generator::save_sheet_as_pdf(entries, layout, output_path, design),
+    }
+}
@@ -1,31 +1,206 @@
 use qrcode::QrCode;
 use image::{ImageBuffer, Luma as ImageLuma, ImageFormat};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Write;
 use std::process::Command;
 
+use crate::sheet::{SheetEntry, SheetLayout};
+use crate::wifi_utils::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
 // Import for SVG-specific color types
 use qrcode::render::svg;
 
 const LATEX_TEMPLATE: &str = include_str!("../resource/layouts/standard.tex");
+const BOOKLET_LATEX_TEMPLATE: &str = include_str!("../resource/layouts/booklet.tex");
+const CARDS_LATEX_TEMPLATE: &str = include_str!("../resource/layouts/cards.tex");
 const TEMP_QR_IMAGE_FILENAME: &str = "qrlan_qr_temp.png";
 const TEMP_LATEX_FILENAME: &str = "qrlan_latex_temp.tex";
 
+/// Characters that carry meaning in the `WIFI:` payload grammar and must be backslash-escaped.
+const WIFI_SPECIAL_CHARACTERS: [char; 5] = ['\\', ';', ',', '"', ':'];
+
+/// Escapes a value for use inside a `WIFI:` payload field.
+///
+/// Special characters (`\`, `;`, `,`, `"`, `:`) are prefixed with a backslash as defined by the
+/// ZXing WIFI grammar. Values consisting only of hex digits are additionally wrapped in double
+/// quotes, as readers would otherwise interpret them as hex-encoded bytes.
+pub fn escape_wifi_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if WIFI_SPECIAL_CHARACTERS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    if is_ambiguous_hex(value) {
+        format!("\"{}\"", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Returns true if a value could be mistaken for a hex-encoded string by a QR reader.
+fn is_ambiguous_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Returns true if a WEP key is a raw hex key (WEP-40, WEP-104 or WEP-232) rather than ASCII text.
+fn is_hex_wep_key(key: &str) -> bool {
+    [10, 26, 58].contains(&key.len()) && is_ambiguous_hex(key)
+}
+
 /// Creates the data string for the WIFI QR code.
-/// Security types: WPA (for WPA/WPA2/WPA3), WEP, nopass (for open networks).
-pub fn generate_qr_code_data(ssid: &str, password: &str, security_type: &str) -> String {
+/// The `T:` value is derived from the security type; WPA3-only networks also get the
+/// transition-disable field `R:` so readers do not fall back to WPA2.
+/// SSID and password are escaped with `escape_wifi_value`; hex WEP keys are left unquoted so readers use them as hex.
+/// Hidden (non-broadcast) networks get the `H:true` field so readers probe for the SSID.
+/// Enterprise networks additionally carry the `E:`, `PH2:`, `I:` and `A:` fields; the credentials are ignored for other types.
+pub fn generate_qr_code_data(
+    ssid: &str,
+    password: &str,
+    security_type: SecurityType,
+    hidden: bool,
+    enterprise: Option<&EnterpriseCredentials>,
+) -> String {
     // Format the Wi-Fi configuration string.
     // SSID and Security Type are mandatory.
-    // Password is included only if it's not empty and security is not 'nopass'.
-    let mut qr_string = format!("WIFI:S:{};T:{};", ssid, security_type);
-    if !password.is_empty() && security_type != "nopass" {
-        qr_string.push_str(&format!("P:{};", password));
+    // Password is included only if it's not empty and the network is not open.
+    let mut qr_string = format!("WIFI:S:{};T:{};", escape_wifi_value(ssid), security_type.qr_value());
+    if !password.is_empty() && security_type.requires_password() {
+        let password_value = if security_type == SecurityType::Wep && is_hex_wep_key(password) {
+            password.to_string() // Hex digits need no escaping.
+        } else {
+            escape_wifi_value(password)
+        };
+        qr_string.push_str(&format!("P:{};", password_value));
+    }
+    if let Some(transition_disable) = security_type.transition_disable() {
+        qr_string.push_str(&format!("R:{};", transition_disable));
+    }
+    if let Some(credentials) = enterprise.filter(|_| security_type == SecurityType::Enterprise) {
+        qr_string.push_str(&format!("E:{};", credentials.eap_method.qr_value()));
+        if let Some(phase2) = credentials.phase2_method {
+            qr_string.push_str(&format!("PH2:{};", phase2.qr_value()));
+        }
+        if let Some(identity) = credentials.identity.as_deref().filter(|i| !i.is_empty()) {
+            qr_string.push_str(&format!("I:{};", escape_wifi_value(identity)));
+        }
+        if let Some(anonymous_identity) = credentials.anonymous_identity.as_deref().filter(|a| !a.is_empty()) {
+            qr_string.push_str(&format!("A:{};", escape_wifi_value(anonymous_identity)));
+        }
+    }
+    if hidden {
+        qr_string.push_str("H:true;");
     }
     qr_string.push(';'); // Terminate the string.
     qr_string
 }
 
+/// Splits a payload at the separator, ignoring backslash-escaped separators. The parts keep their escapes.
+fn split_unescaped(data: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in data.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(&data[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&data[start..]);
+    parts
+}
+
+/// Reverses `escape_wifi_value`: removes the quotes around hex values and the backslash escapes.
+fn unescape_wifi_value(value: &str) -> String {
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                unescaped.push(next);
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Parses a `WIFI:` payload, as created by `generate_qr_code_data`, back into a network.
+/// WPA payloads with the transition-disable field `R:1` are WPA3-only networks.
+///
+/// # Errors
+/// Returns an error if the data is not a Wi-Fi payload or contains unknown values.
+pub fn parse_qr_code_data(data: &str) -> Result<WifiNetwork, String> {
+    let fields = data
+        .get(..5)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("WIFI:"))
+        .map(|_| &data[5..])
+        .ok_or("The QR code does not contain a Wi-Fi configuration (missing 'WIFI:' prefix).")?;
+
+    let mut ssid = None;
+    let mut password = None;
+    let mut security_type = SecurityType::Open;
+    let mut transition_disable = false;
+    let mut hidden = false;
+    let mut eap_method = None;
+    let mut phase2_method = None;
+    let mut identity = None;
+    let mut anonymous_identity = None;
+
+    for field in split_unescaped(fields, ';').into_iter().filter(|field| !field.is_empty()) {
+        let (key, raw_value) = field.split_once(':').ok_or(format!("Invalid field '{}' in Wi-Fi payload.", field))?;
+        let value = unescape_wifi_value(raw_value);
+        match key.to_uppercase().as_str() {
+            "S" => ssid = Some(value),
+            "P" => password = Some(value).filter(|p| !p.is_empty()),
+            "T" if value.is_empty() => security_type = SecurityType::Open,
+            "T" => security_type = value.parse()?,
+            "R" => transition_disable = value.trim() == "1",
+            "H" => hidden = value.eq_ignore_ascii_case("true"),
+            "E" => eap_method = Some(EapMethod::from_name(&value).ok_or(format!("Unknown EAP method '{}'.", value))?),
+            "PH2" => phase2_method = Some(Phase2Method::from_name(&value).ok_or(format!("Unknown phase 2 method '{}'.", value))?),
+            "I" => identity = Some(value),
+            "A" => anonymous_identity = Some(value),
+            _ => {} // Ignore fields of newer or vendor-specific payload versions.
+        }
+    }
+
+    if transition_disable && security_type == SecurityType::WpaPersonal {
+        security_type = SecurityType::Wpa3Sae;
+    }
+    let enterprise = match (security_type, eap_method) {
+        (SecurityType::Enterprise, Some(eap_method)) => Some(EnterpriseCredentials {
+            eap_method,
+            phase2_method,
+            identity,
+            anonymous_identity,
+        }),
+        (SecurityType::Enterprise, None) => return Err("Enterprise Wi-Fi payload is missing the EAP method (E:).".into()),
+        _ => None,
+    };
+
+    Ok(WifiNetwork {
+        ssid: ssid.ok_or("Wi-Fi payload is missing the SSID (S:).")?,
+        password,
+        security_type: Some(security_type),
+        hidden,
+        enterprise,
+    })
+}
+
 /// Creates a QR code image from the given data.
 /// Returns an Option containing the ImageBuffer on success, or None on failure.
 pub fn create_qr_image(data: &str) -> Option<ImageBuffer<ImageLuma<u8>, Vec<u8>>> {
@@ -76,48 +251,10 @@ pub fn save_qr_as_svg(
 
     Ok(())
 }
-
-/// Saves the QR code as a PDF by generating a .tex file and compiling it with pdflatex.
-///
-/// # Arguments
-/// * `qr_image_buffer` - Buffer containing the QR code image.
-/// * `output_pdf_path` - Path where the final PDF will be saved.
-/// * `title` - Title to be displayed in the PDF above the QR code.
-/// * `custom_template_path` - Optional path to a custom LaTeX template.
-///
-/// # Errors
-/// Returns an error if any step of the PDF generation process fails (e.g., file I/O, pdflatex execution).
-pub fn save_qr_as_pdf(
-    qr_image_buffer: &ImageBuffer<ImageLuma<u8>, Vec<u8>>,
-    output_pdf_path: &Path,
-    title: &str,
-    custom_template_path: Option<&String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Ensure the output directory exists.
-    let output_dir = output_pdf_path.parent().ok_or_else(|| {
-        Box::<dyn std::error::Error>::from("Output PDF path does not have a parent directory.")
-    })?;
-
-    if !output_dir.exists() {
-        fs::create_dir_all(output_dir)
-            .map_err(|e| format!("Failed to create output directory '{:?}': {}", output_dir, e))?;
-    }
-
-    // Define paths for temporary files within the output directory.
-    let temp_qr_image_path = output_dir.join(TEMP_QR_IMAGE_FILENAME);
-    let temp_latex_file_path = output_dir.join(TEMP_LATEX_FILENAME);
-
-    // 1. Save QR code image temporarily.
-    qr_image_buffer.save_with_format(&temp_qr_image_path, ImageFormat::Png)
-        .map_err(|e| format!("Failed to save temporary QR image to '{:?}': {}", temp_qr_image_path, e))?;
-
-    // 2. Prepare LaTeX content.
-    // For LaTeX, use only the filename for the image path as it's in the same directory as the .tex file.
-    let qr_image_filename_for_latex = TEMP_QR_IMAGE_FILENAME;
-
-    // Basic LaTeX escaping for the title.
-    // A more robust solution might involve a dedicated LaTeX escaping library or more comprehensive replacements.
-    let escaped_title = title
+// Basic LaTeX escaping for text inserted into a template.
+// A more robust solution might involve a dedicated LaTeX escaping library or more comprehensive replacements.
+fn escape_latex(text: &str) -> String {
+    text
         .replace("\\", "\\textbackslash{}") // Must be first, replace backslash string with LaTeX command
         .replace('{', "\\{")
         .replace('}', "\\}")
@@ -127,35 +264,59 @@ pub fn save_qr_as_pdf(
         .replace('%', "\\%")
         .replace('$', "\\$")
         .replace('#', "\\#")
-        .replace('~', "\\textasciitilde{}");
+        .replace('~', "\\textasciitilde{}")
+}
 
-    let latex_content: String;
-    if let Some(template_path_str) = custom_template_path {
-        let path = Path::new(template_path_str);
-        if !path.exists() || !path.is_file() {
-            return Err(format!("Custom LaTeX template file not found or is not a file: {}", template_path_str).into());
-        }
-        latex_content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read custom LaTeX template '{}': {}", template_path_str, e))?;
-        if !latex_content.contains("{{QRLAN_PDF_TITLE}}") || !latex_content.contains("{{QR_CODE_IMAGE_PATH}}") {
-            return Err("Custom LaTeX template is missing required placeholders: {{QRLAN_PDF_TITLE}} and/or {{QR_CODE_IMAGE_PATH}}".into());
-        }
-    } else {
-        latex_content = LATEX_TEMPLATE.to_string();
+// Reads a custom LaTeX template and checks that it contains all required placeholders.
+fn read_custom_template(template_path_str: &str, required_placeholders: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let path = Path::new(template_path_str);
+    if !path.exists() || !path.is_file() {
+        return Err(format!("Custom LaTeX template file not found or is not a file: {}", template_path_str).into());
+    }
+    let latex_content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read custom LaTeX template '{}': {}", template_path_str, e))?;
+    let missing: Vec<&str> = required_placeholders
+        .iter()
+        .copied()
+        .filter(|placeholder| !latex_content.contains(placeholder))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Custom LaTeX template is missing required placeholders: {}", missing.join(", ")).into());
     }
+    Ok(latex_content)
+}
 
-    let processed_template = latex_content
-        .replace("{{QRLAN_PDF_TITLE}}", &escaped_title) // Replace title placeholder
-        .replace("{{QR_CODE_IMAGE_PATH}}", qr_image_filename_for_latex); // Replace image path placeholder
+// Ensures the output directory of the PDF exists and returns it.
+fn prepare_output_dir(output_pdf_path: &Path) -> Result<&Path, Box<dyn std::error::Error>> {
+    let output_dir = output_pdf_path.parent().ok_or_else(|| {
+        Box::<dyn std::error::Error>::from("Output PDF path does not have a parent directory.")
+    })?;
 
-    // 3. Write temporary .tex file.
+    if !output_dir.exists() {
+        fs::create_dir_all(output_dir)
+            .map_err(|e| format!("Failed to create output directory '{:?}': {}", output_dir, e))?;
+    }
+    Ok(output_dir)
+}
+
+// Writes the processed template next to the output PDF, compiles it with pdflatex and moves the result
+// to the output path. The temporary images referenced by the template are removed afterwards.
+fn compile_latex(
+    processed_template: &str,
+    output_pdf_path: &Path,
+    temp_image_paths: &[PathBuf],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_dir = prepare_output_dir(output_pdf_path)?;
+    let temp_latex_file_path = output_dir.join(TEMP_LATEX_FILENAME);
+
+    // Write temporary .tex file.
     let mut temp_latex_file = fs::File::create(&temp_latex_file_path)
         .map_err(|e| format!("Failed to create temporary LaTeX file '{:?}': {}", temp_latex_file_path, e))?;
     temp_latex_file.write_all(processed_template.as_bytes())
         .map_err(|e| format!("Failed to write to temporary LaTeX file '{:?}': {}", temp_latex_file_path, e))?;
     drop(temp_latex_file); // Ensure the file is closed before pdflatex tries to access it.
 
-    // 4. Compile .tex file with pdflatex.
+    // Compile .tex file with pdflatex.
     // The -output-directory flag ensures that pdflatex writes its output (including .log, .aux, .pdf)
     // to the specified directory, which is the same directory where our temporary .tex and .png files are.
     let pdflatex_command_output = Command::new("pdflatex")
@@ -183,7 +344,7 @@ pub fn save_qr_as_pdf(
         ).into());
     }
 
-    // 5. Rename generated PDF to the final output path.
+    // Rename generated PDF to the final output path.
     // The generated PDF will have the same base name as the .tex file.
     let generated_pdf_filename = temp_latex_file_path.file_stem().unwrap_or_default().to_str().unwrap_or("").to_string() + ".pdf";
     let generated_pdf_in_output_dir = output_dir.join(generated_pdf_filename);
@@ -196,9 +357,11 @@ pub fn save_qr_as_pdf(
     fs::rename(&generated_pdf_in_output_dir, output_pdf_path)
         .map_err(|e| format!("Failed to rename temporary PDF '{:?}' to '{:?}': {}", generated_pdf_in_output_dir, output_pdf_path, e))?;
 
-    // 6. Clean up temporary files.
+    // Clean up temporary files.
     // Use .ok() to ignore errors during cleanup, as these are not critical.
-    fs::remove_file(&temp_qr_image_path).ok();
+    for temp_image_path in temp_image_paths {
+        fs::remove_file(temp_image_path).ok();
+    }
     fs::remove_file(&temp_latex_file_path).ok();
     // pdflatex generates several auxiliary files; attempt to remove them.
     fs::remove_file(temp_latex_file_path.with_extension("aux")).ok();
@@ -209,3 +372,240 @@ pub fn save_qr_as_pdf(
 
     Ok(())
 }
+
+/// Saves the QR code as a PDF by generating a .tex file and compiling it with pdflatex.
+///
+/// # Arguments
+/// * `qr_image_buffer` - Buffer containing the QR code image.
+/// * `output_pdf_path` - Path where the final PDF will be saved.
+/// * `title` - Title to be displayed in the PDF above the QR code.
+/// * `custom_template_path` - Optional path to a custom LaTeX template.
+///
+/// # Errors
+/// Returns an error if any step of the PDF generation process fails (e.g., file I/O, pdflatex execution).
+pub fn save_qr_as_pdf(
+    qr_image_buffer: &ImageBuffer<ImageLuma<u8>, Vec<u8>>,
+    output_pdf_path: &Path,
+    title: &str,
+    custom_template_path: Option<&String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let latex_content = match custom_template_path {
+        Some(template_path_str) => read_custom_template(template_path_str, &["{{QRLAN_PDF_TITLE}}", "{{QR_CODE_IMAGE_PATH}}"])?,
+        None => LATEX_TEMPLATE.to_string(),
+    };
+
+    // Save QR code image temporarily, next to the .tex file.
+    let temp_qr_image_path = prepare_output_dir(output_pdf_path)?.join(TEMP_QR_IMAGE_FILENAME);
+    qr_image_buffer.save_with_format(&temp_qr_image_path, ImageFormat::Png)
+        .map_err(|e| format!("Failed to save temporary QR image to '{:?}': {}", temp_qr_image_path, e))?;
+
+    // For LaTeX, use only the filename for the image path as it's in the same directory as the .tex file.
+    let processed_template = latex_content
+        .replace("{{QRLAN_PDF_TITLE}}", &escape_latex(title)) // Replace title placeholder
+        .replace("{{QR_CODE_IMAGE_PATH}}", TEMP_QR_IMAGE_FILENAME); // Replace image path placeholder
+
+    compile_latex(&processed_template, output_pdf_path, &[temp_qr_image_path])
+}
+
+/// Saves several QR codes into one PDF by filling a sheet template and compiling it with pdflatex.
+///
+/// Sheet templates use these placeholders:
+/// * `{{QRLAN_PAPER_WIDTH}}`, `{{QRLAN_PAPER_HEIGHT}}` - Paper size in millimeters.
+/// * `{{QRLAN_CARD_WIDTH}}`, `{{QRLAN_CARD_HEIGHT}}` - Size of one card (grid cell) in millimeters.
+/// * `{{QRLAN_QR_SIZE}}` - Side length of the QR code on a card in millimeters.
+/// * `{{QRLAN_PAGES}}` - The pages: one `qrlanpage` environment per page, containing one
+///   `\qrlancard{x}{y}{title}{image}` per network, where (x, y) is the lower left corner of the card
+///   in millimeters from the lower left corner of the page.
+///
+/// # Arguments
+/// * `entries` - The networks to include, in order.
+/// * `layout` - Paper size and card grid.
+/// * `output_pdf_path` - Path where the final PDF will be saved.
+/// * `custom_template_path` - Optional path to a custom sheet template.
+///
+/// # Errors
+/// Returns an error if a QR code cannot be generated or any step of the PDF generation fails.
+pub fn save_sheet_as_pdf(
+    entries: &[SheetEntry],
+    layout: &SheetLayout,
+    output_pdf_path: &Path,
+    custom_template_path: Option<&String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let latex_content = match custom_template_path {
+        Some(template_path_str) => read_custom_template(template_path_str, &["{{QRLAN_PAGES}}"])?,
+        None if layout.cut_marks => CARDS_LATEX_TEMPLATE.to_string(),
+        None => BOOKLET_LATEX_TEMPLATE.to_string(),
+    };
+
+    let output_dir = prepare_output_dir(output_pdf_path)?;
+    let mut temp_image_paths = Vec::new();
+    let mut pages = String::new();
+    for (page_index, page_entries) in entries.chunks(layout.cards_per_page()).enumerate() {
+        pages.push_str("\\begin{qrlanpage}\n");
+        for (index_on_page, entry) in page_entries.iter().enumerate() {
+            let image_filename = format!("qrlan_qr_temp_{}.png", page_index * layout.cards_per_page() + index_on_page);
+            let image_path = output_dir.join(&image_filename);
+            let qr_image = create_qr_image(&entry.qr_data)
+                .ok_or_else(|| format!("QR code image creation failed for '{}'", entry.title))?;
+            qr_image.save_with_format(&image_path, ImageFormat::Png)
+                .map_err(|e| format!("Failed to save temporary QR image to '{:?}': {}", image_path, e))?;
+            temp_image_paths.push(image_path);
+
+            let (x, y) = layout.card_origin(index_on_page);
+            pages.push_str(&format!(
+                "\\qrlancard{{{:.2}}}{{{:.2}}}{{{}}}{{{}}}\n",
+                x,
+                y,
+                escape_latex(&entry.title),
+                image_filename
+            ));
+        }
+        pages.push_str("\\end{qrlanpage}\n");
+    }
+
+    let (card_width, card_height) = layout.card_size();
+    let processed_template = latex_content
+        .replace("{{QRLAN_PAPER_WIDTH}}", &format!("{:.2}", layout.paper_width))
+        .replace("{{QRLAN_PAPER_HEIGHT}}", &format!("{:.2}", layout.paper_height))
+        .replace("{{QRLAN_CARD_WIDTH}}", &format!("{:.2}", card_width))
+        .replace("{{QRLAN_CARD_HEIGHT}}", &format!("{:.2}", card_height))
+        .replace("{{QRLAN_QR_SIZE}}", &format!("{:.2}", layout.qr_size()))
+        .replace("{{QRLAN_PAGES}}", &pages);
+
+    compile_latex(&processed_template, output_pdf_path, &temp_image_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peap_credentials() -> EnterpriseCredentials {
+        EnterpriseCredentials {
+            eap_method: EapMethod::Peap,
+            phase2_method: Some(Phase2Method::Mschapv2),
+            identity: Some("alice;admin".to_string()),
+            anonymous_identity: Some("anonymous@corp".to_string()),
+        }
+    }
+
+    #[test]
+    fn escape_wifi_value_escapes_special_characters() {
+        assert_eq!(escape_wifi_value(r#"a;b,c:d\e"f"#), r#"a\;b\,c\:d\\e\"f"#);
+        assert_eq!(escape_wifi_value("plain text"), "plain text");
+    }
+
+    #[test]
+    fn escape_wifi_value_quotes_hex_values() {
+        assert_eq!(escape_wifi_value("CAFE"), "\"CAFE\"");
+        assert_eq!(escape_wifi_value("12345678"), "\"12345678\"");
+        assert_eq!(escape_wifi_value("CAFEX"), "CAFEX");
+        assert_eq!(escape_wifi_value(""), "");
+    }
+
+    #[test]
+    fn generate_qr_code_data_escapes_ssid_and_password() {
+        for special in [";", ",", ":", "\\", "\""] {
+            let ssid = format!("Net{}work", special);
+            let password = format!("pass{}word", special);
+            let data = generate_qr_code_data(&ssid, &password, SecurityType::WpaPersonal, false, None);
+            assert_eq!(data, format!("WIFI:S:Net\\{}work;T:WPA;P:pass\\{}word;;", special, special));
+        }
+    }
+
+    #[test]
+    fn generate_qr_code_data_keeps_hex_wep_keys_unquoted() {
+        let data = generate_qr_code_data("Legacy", "0102030405", SecurityType::Wep, false, None);
+        assert_eq!(data, "WIFI:S:Legacy;T:WEP;P:0102030405;;");
+        let data = generate_qr_code_data("Legacy", "0102030405060708090a0b0c0d", SecurityType::Wep, false, None);
+        assert_eq!(data, "WIFI:S:Legacy;T:WEP;P:0102030405060708090a0b0c0d;;");
+
+        // Other lengths are ASCII keys or passphrases, and WPA passphrases are always text.
+        let data = generate_qr_code_data("Legacy", "12345", SecurityType::Wep, false, None);
+        assert_eq!(data, "WIFI:S:Legacy;T:WEP;P:\"12345\";;");
+        let data = generate_qr_code_data("Home", "0102030405", SecurityType::WpaPersonal, false, None);
+        assert_eq!(data, "WIFI:S:Home;T:WPA;P:\"0102030405\";;");
+    }
+
+    #[test]
+    fn generate_qr_code_data_omits_password_of_open_networks() {
+        assert_eq!(generate_qr_code_data("Guest", "ignored", SecurityType::Open, false, None), "WIFI:S:Guest;T:nopass;;");
+        assert_eq!(generate_qr_code_data("Guest", "", SecurityType::Owe, false, None), "WIFI:S:Guest;T:nopass;;");
+    }
+
+    #[test]
+    fn generate_qr_code_data_adds_hidden_and_transition_disable_fields() {
+        let data = generate_qr_code_data("Lab", "secret", SecurityType::Wpa3Sae, true, None);
+        assert_eq!(data, "WIFI:S:Lab;T:WPA;P:secret;R:1;H:true;;");
+        let data = generate_qr_code_data("Lab", "secret", SecurityType::Wpa2Wpa3Transition, false, None);
+        assert_eq!(data, "WIFI:S:Lab;T:WPA;P:secret;;");
+    }
+
+    #[test]
+    fn generate_qr_code_data_adds_enterprise_fields() {
+        let data = generate_qr_code_data("Corp", "pw", SecurityType::Enterprise, false, Some(&peap_credentials()));
+        assert_eq!(data, r"WIFI:S:Corp;T:WPA2-EAP;P:pw;E:PEAP;PH2:MSCHAPV2;I:alice\;admin;A:anonymous@corp;;");
+    }
+
+    #[test]
+    fn generate_qr_code_data_ignores_enterprise_fields_of_other_types() {
+        let data = generate_qr_code_data("Corp", "pw", SecurityType::WpaPersonal, false, Some(&peap_credentials()));
+        assert_eq!(data, "WIFI:S:Corp;T:WPA;P:pw;;");
+    }
+
+    #[test]
+    fn parse_qr_code_data_round_trips_office_passphrases() {
+        let passphrases = ["Meeting;Room;2", "a;b,c:d\\e\"f", "ends with;", ";starts", "DEADBEEF", "café;Ü"];
+        for passphrase in passphrases {
+            let ssid = format!("Office;{}", passphrase);
+            let data = generate_qr_code_data(&ssid, passphrase, SecurityType::WpaPersonal, false, None);
+            let network = parse_qr_code_data(&data).unwrap();
+            assert_eq!(network.ssid, ssid);
+            assert_eq!(network.password.as_deref(), Some(passphrase));
+            assert_eq!(network.security_type, Some(SecurityType::WpaPersonal));
+        }
+    }
+
+    #[test]
+    fn parse_qr_code_data_round_trips_all_fields() {
+        let data = generate_qr_code_data("Lab", "secret", SecurityType::Wpa3Sae, true, None);
+        let network = parse_qr_code_data(&data).unwrap();
+        assert_eq!(network.security_type, Some(SecurityType::Wpa3Sae));
+        assert!(network.hidden);
+
+        let data = generate_qr_code_data("Corp", "p;w", SecurityType::Enterprise, false, Some(&peap_credentials()));
+        let network = parse_qr_code_data(&data).unwrap();
+        assert_eq!(network.security_type, Some(SecurityType::Enterprise));
+        assert_eq!(network.password.as_deref(), Some("p;w"));
+        let enterprise = network.enterprise.unwrap();
+        assert_eq!(enterprise.eap_method, EapMethod::Peap);
+        assert_eq!(enterprise.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(enterprise.identity.as_deref(), Some("alice;admin"));
+        assert_eq!(enterprise.anonymous_identity.as_deref(), Some("anonymous@corp"));
+
+        let network = parse_qr_code_data(&generate_qr_code_data("Guest", "", SecurityType::Open, false, None)).unwrap();
+        assert_eq!(network.security_type, Some(SecurityType::Open));
+        assert_eq!(network.password, None);
+    }
+
+    #[test]
+    fn parse_qr_code_data_accepts_payloads_of_other_generators() {
+        // Lowercase prefix, different field order, unknown fields and no terminating ';;'.
+        let network = parse_qr_code_data(r#"wifi:T:WPA;P:"12345678";X:vendor;S:Home\ Net;H:TRUE"#).unwrap();
+        assert_eq!(network.ssid, "Home Net"); // Unnecessary escapes are removed.
+        assert_eq!(network.password.as_deref(), Some("12345678"));
+        assert_eq!(network.security_type, Some(SecurityType::WpaPersonal));
+        assert!(network.hidden);
+
+        let network = parse_qr_code_data("WIFI:S:Cafe;T:;P:;;").unwrap();
+        assert_eq!(network.security_type, Some(SecurityType::Open));
+        assert_eq!(network.password, None);
+    }
+
+    #[test]
+    fn parse_qr_code_data_rejects_invalid_payloads() {
+        assert!(parse_qr_code_data("https://example.com").is_err());
+        assert!(parse_qr_code_data("WIFI:T:WPA;P:secret;;").is_err());
+        assert!(parse_qr_code_data("WIFI:S:Corp;T:WPA2-EAP;;").is_err());
+        assert!(parse_qr_code_data("WIFI:S:Net;T:FOO;;").is_err());
+    }
+}
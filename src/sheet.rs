@@ -0,0 +1,141 @@
+use crate::cli::Paper;
+
+// Layout constants in millimeters. Booklet pages follow resource/layouts/standard.tex.
+const BOOKLET_QR_WIDTH_RATIO: f64 = 0.6; // QR code width relative to the page width.
+const BOOKLET_TITLE_GAP_RATIO: f64 = 0.06; // Space between title and QR code relative to the page width.
+const BOOKLET_TITLE_FONT_SIZE_PT: f64 = 20.74; // \LARGE at 12pt.
+const CARD_SHEET_MARGIN_MM: f64 = 10.0; // Margin around the card grid, leaves room for printers.
+const BOOKLET_PADDING_MM: f64 = 10.0; // Longer titles are scaled down or wrapped to stay inside.
+const CARD_PADDING_MM: f64 = 4.0; // Space between the cut line and the card content.
+const CARD_TITLE_FONT_SIZE_PT: f64 = 12.0;
+const MIN_QR_SIZE_MM: f64 = 20.0; // Smaller printed QR codes are hard to scan, especially with long payloads.
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// One network of a multi-network document.
+pub struct SheetEntry {
+    pub title: String,
+    pub qr_data: String, // The WIFI payload created by `generate_qr_code_data`.
+}
+
+/// Arrangement of several QR codes in one PDF document: a grid of cards per page.
+/// A booklet is a 1x1 grid without cut marks, i.e. one network per page.
+#[derive(Debug)]
+pub struct SheetLayout {
+    pub paper_width: f64,
+    pub paper_height: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub cut_marks: bool,
+}
+
+/// Returns the width and height of the paper in millimeters.
+pub fn paper_size(paper: Paper) -> (f64, f64) {
+    match paper {
+        Paper::A4 => (210.0, 297.0),
+        Paper::Letter => (215.9, 279.4),
+    }
+}
+
+impl SheetLayout {
+    /// One network per page, laid out like the standard single-page template.
+    pub fn booklet(paper: Paper) -> SheetLayout {
+        let (paper_width, paper_height) = paper_size(paper);
+        SheetLayout { paper_width, paper_height, columns: 1, rows: 1, cut_marks: false }
+    }
+
+    /// A grid of cards per page, separated by cut marks.
+    ///
+    /// # Errors
+    /// Returns an error if the cards are too small for a QR code of the minimum scannable size.
+    pub fn cards(paper: Paper, columns: usize, rows: usize) -> Result<SheetLayout, String> {
+        let (paper_width, paper_height) = paper_size(paper);
+        let layout = SheetLayout { paper_width, paper_height, columns, rows, cut_marks: true };
+        if layout.qr_size() < MIN_QR_SIZE_MM {
+            return Err(format!(
+                "The grid {}x{} is too dense: the QR codes would be {:.1} mm wide, but at least {:.0} mm are needed to scan them. Use fewer columns or rows.",
+                columns,
+                rows,
+                layout.qr_size().max(0.0),
+                MIN_QR_SIZE_MM
+            ));
+        }
+        Ok(layout)
+    }
+
+    pub fn cards_per_page(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    fn margin(&self) -> f64 {
+        if self.cut_marks { CARD_SHEET_MARGIN_MM } else { 0.0 }
+    }
+
+    /// Returns the width and height of one card in millimeters.
+    pub fn card_size(&self) -> (f64, f64) {
+        (
+            (self.paper_width - 2.0 * self.margin()) / self.columns as f64,
+            (self.paper_height - 2.0 * self.margin()) / self.rows as f64,
+        )
+    }
+
+    /// Returns the lower left corner of the card at the given position on its page, filled row by row from the top.
+    pub fn card_origin(&self, index_on_page: usize) -> (f64, f64) {
+        let (card_width, card_height) = self.card_size();
+        let column = index_on_page % self.columns;
+        let row = index_on_page / self.columns;
+        (
+            self.margin() + column as f64 * card_width,
+            self.paper_height - self.margin() - (row + 1) as f64 * card_height,
+        )
+    }
+
+    /// Returns the minimum space between the card border and its content in millimeters.
+    pub fn padding(&self) -> f64 {
+        if self.cut_marks { CARD_PADDING_MM } else { BOOKLET_PADDING_MM }
+    }
+
+    /// Returns the font size of the card title in points.
+    pub fn title_font_size(&self) -> f64 {
+        if self.cut_marks { CARD_TITLE_FONT_SIZE_PT } else { BOOKLET_TITLE_FONT_SIZE_PT }
+    }
+
+    /// Returns the space between title and QR code in millimeters.
+    pub fn title_gap(&self) -> f64 {
+        if self.cut_marks { self.padding() / 2.0 } else { self.paper_width * BOOKLET_TITLE_GAP_RATIO }
+    }
+
+    /// Returns the height of the title line in millimeters.
+    pub fn title_height(&self) -> f64 {
+        self.title_font_size() / PT_PER_MM
+    }
+
+    /// Returns the side length of the QR code (including its quiet zone) in millimeters.
+    pub fn qr_size(&self) -> f64 {
+        if !self.cut_marks {
+            return self.paper_width * BOOKLET_QR_WIDTH_RATIO;
+        }
+        let (card_width, card_height) = self.card_size();
+        (card_width - 2.0 * self.padding()).min(card_height - 2.0 * self.padding() - self.title_height() - self.title_gap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_fit_a_scannable_qr_code() {
+        let layout = SheetLayout::cards(Paper::A4, 2, 4).unwrap();
+        assert_eq!(layout.cards_per_page(), 8);
+        assert!(layout.qr_size() > 50.0);
+        assert!(SheetLayout::cards(Paper::A4, 4, 8).is_ok()); // About 20.4 mm.
+        assert!(SheetLayout::cards(Paper::Letter, 4, 8).is_err()); // About 18.2 mm.
+    }
+
+    #[test]
+    fn cards_reject_dense_grids() {
+        let error = SheetLayout::cards(Paper::A4, 2, 40).unwrap_err();
+        assert!(error.starts_with("The grid 2x40 is too dense: the QR codes would be 0.0 mm wide"), "{}", error);
+        assert!(SheetLayout::cards(Paper::A4, 10, 2).is_err());
+    }
+}
@@ -1,47 +1,243 @@
-#[cfg(target_os = "macos")]
+mod command; // Runs the external commands of the system backends; replayed from fixtures in tests.
+#[cfg(any(target_os = "macos", test))] // The backends are tested on all platforms with recorded command output.
 mod macos;
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", test))]
 mod windows;
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(target_os = "linux")]
+mod iwd;
+#[cfg(target_os = "linux")]
+mod nm_keyfile;
+#[cfg(target_os = "linux")]
+mod ini;
+pub mod wpa_supplicant; // Also used to import configuration files on all platforms.
+pub mod hostapd;
+pub mod uci; // OpenWrt wireless configuration.
+pub mod netplan;
+pub mod android; // WifiConfigStore.xml backups, importable on all platforms.
+pub mod apple_profile; // Configuration profiles and Wi-Fi plists, importable on all platforms.
+pub mod windows_profile; // WLANProfile XML, used by the Windows backend and for imports on all platforms.
+
+mod source;
 
-#[cfg(target_os = "macos")]
-pub use macos::get_known_networks;
-#[cfg(target_os = "macos")]
-pub use macos::fetch_password_for_ssid; // Export new function
-#[cfg(target_os = "windows")]
-pub use windows::get_known_networks;
-#[cfg(target_os = "windows")]
-pub use windows::fetch_password_for_ssid; // Export for Windows
+pub use source::{current_ssid, fetch_password, find_source, probe, read_sources, registry, FileSource, WifiSource};
 #[cfg(target_os = "linux")]
-pub use linux::get_known_networks;
+pub use linux::{add_connection, connection_add_args}; // Create NetworkManager connections
+
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Clone)]
 pub struct WifiNetwork {
     pub ssid: String,
-    pub password: Option<String>, 
-    pub security_type: Option<String>, 
-    // In the future, security type etc. could also be automatically detected here.
+    pub password: Option<String>, // For enterprise networks, this is the 802.1X user password.
+    pub security_type: Option<SecurityType>, 
+    pub hidden: bool, // True if the network does not broadcast its SSID.
+    pub enterprise: Option<EnterpriseCredentials>, // Only set for 802.1X (WPA2/WPA3-Enterprise) networks.
+}
+
+impl WifiNetwork {
+    /// Creates a network from the settings read by a backend or importer.
+    /// Enterprise networks without supported EAP settings cannot be encoded: their security type is left
+    /// undetermined and the 802.1X password is dropped.
+    pub fn new(
+        ssid: String,
+        password: Option<String>,
+        security_type: Option<SecurityType>,
+        hidden: bool,
+        enterprise: Option<EnterpriseCredentials>,
+    ) -> WifiNetwork {
+        if security_type == Some(SecurityType::Enterprise) && enterprise.is_none() {
+            return WifiNetwork { hidden, ..WifiNetwork::from_ssid(ssid) };
+        }
+        WifiNetwork { ssid, password, security_type, hidden, enterprise }
+    }
+
+    /// Creates a network with only the SSID known, e.g. when entered manually.
+    pub fn from_ssid(ssid: String) -> WifiNetwork {
+        WifiNetwork { ssid, password: None, security_type: None, hidden: false, enterprise: None }
+    }
+}
+
+/// Security (authentication) type of a Wi-Fi network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityType {
+    Open,
+    Owe,                 // Wi-Fi Enhanced Open (Opportunistic Wireless Encryption).
+    Wep,
+    WpaPersonal,         // WPA/WPA2-Personal (PSK).
+    Wpa3Sae,             // WPA3-Personal only (SAE).
+    Wpa2Wpa3Transition,  // WPA2/WPA3-Personal transition mode (PSK and SAE).
+    Enterprise,          // WPA2/WPA3-Enterprise (802.1X).
+}
+
+impl SecurityType {
+    /// Returns the value used for the `T:` field of the QR code.
+    pub fn qr_value(&self) -> &'static str {
+        match self {
+            // OWE has no dedicated QR value; readers upgrade to OWE automatically when offered.
+            SecurityType::Open | SecurityType::Owe => "nopass",
+            SecurityType::Wep => "WEP",
+            SecurityType::WpaPersonal | SecurityType::Wpa3Sae | SecurityType::Wpa2Wpa3Transition => "WPA",
+            SecurityType::Enterprise => "WPA2-EAP",
+        }
+    }
+
+    /// Returns the value of the transition-disable (`R:`) field of the QR code, if any.
+    /// WPA3-only networks set bit 0 so readers do not fall back to WPA2 when joining.
+    pub fn transition_disable(&self) -> Option<u8> {
+        match self {
+            SecurityType::Wpa3Sae => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Returns true if joining the network requires a password.
+    pub fn requires_password(&self) -> bool {
+        !matches!(self, SecurityType::Open | SecurityType::Owe)
+    }
 }
 
-// Fallback for unsupported operating systems or if no specific implementation is available.
-#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
-    println!("Wi-Fi network retrieval for the current operating system is not implemented.");
-    // Return an empty vector to allow manual SSID input in main.rs.
-    Ok(Vec::new())
+impl fmt::Display for SecurityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SecurityType::Open => "Open",
+            SecurityType::Owe => "Enhanced Open (OWE)",
+            SecurityType::Wep => "WEP",
+            SecurityType::WpaPersonal => "WPA/WPA2-Personal",
+            SecurityType::Wpa3Sae => "WPA3-Personal (SAE)",
+            SecurityType::Wpa2Wpa3Transition => "WPA2/WPA3-Personal",
+            SecurityType::Enterprise => "WPA2/WPA3-Enterprise",
+        };
+        write!(f, "{}", name)
+    }
 }
 
-// Dummy implementations for password fetching on non-macOS/non-Windows platforms.
-// These can be expanded with actual implementations for Linux in the future.
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn fetch_password_for_ssid(_ssid: &str) -> Result<Option<String>, String> {
-    // This function is primarily intended for macOS (Keychain access) and Windows (netsh).
-    // For other OS, a general solution is complex and might require specific privileges or tools.
-    // Returning Ok(None) indicates that the password was not automatically fetched.
-    Ok(None) 
+impl FromStr for SecurityType {
+    type Err = String;
+
+    /// Parses user input such as "WPA", "WPA3", "WEP" or "nopass" (case-insensitive).
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "nopass" | "open" | "none" => Ok(SecurityType::Open),
+            "owe" => Ok(SecurityType::Owe),
+            "wep" => Ok(SecurityType::Wep),
+            "wpa" | "wpa2" | "wpa-psk" | "wpa2-psk" => Ok(SecurityType::WpaPersonal),
+            "wpa3" | "sae" => Ok(SecurityType::Wpa3Sae),
+            "wpa2/wpa3" | "wpa2-wpa3" | "transition" => Ok(SecurityType::Wpa2Wpa3Transition),
+            "wpa2-eap" | "eap" | "enterprise" => Ok(SecurityType::Enterprise),
+            _ => Err(format!("Unknown security type '{}'.", input.trim())),
+        }
+    }
+}
+
+/// Outer EAP method of an enterprise network (the `E:` field of the QR code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EapMethod {
+    Peap,
+    Ttls,
+    Tls,
+    Pwd,
+    Leap,
+    Fast,
+    Sim,
+    Aka,
+    AkaPrime,
+}
+
+impl EapMethod {
+    /// Parses an EAP method name as used by NetworkManager, wpa_supplicant and most profile formats.
+    pub fn from_name(name: &str) -> Option<EapMethod> {
+        match name.trim().to_lowercase().as_str() {
+            "peap" => Some(EapMethod::Peap),
+            "ttls" => Some(EapMethod::Ttls),
+            "tls" => Some(EapMethod::Tls),
+            "pwd" => Some(EapMethod::Pwd),
+            "leap" => Some(EapMethod::Leap),
+            "fast" => Some(EapMethod::Fast),
+            "sim" => Some(EapMethod::Sim),
+            "aka" => Some(EapMethod::Aka),
+            "aka'" | "aka-prime" | "aka_prime" | "akaprime" => Some(EapMethod::AkaPrime),
+            _ => None,
+        }
+    }
+
+    /// Maps an IANA EAP type number, as used by Windows and Apple profiles, to an EAP method.
+    pub fn from_type_number(eap_type: u64) -> Option<EapMethod> {
+        match eap_type {
+            13 => Some(EapMethod::Tls),
+            17 => Some(EapMethod::Leap),
+            18 => Some(EapMethod::Sim),
+            21 => Some(EapMethod::Ttls),
+            23 => Some(EapMethod::Aka),
+            25 => Some(EapMethod::Peap),
+            43 => Some(EapMethod::Fast),
+            50 => Some(EapMethod::AkaPrime),
+            52 => Some(EapMethod::Pwd),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used for the `E:` field of the QR code.
+    pub fn qr_value(&self) -> &'static str {
+        match self {
+            EapMethod::Peap => "PEAP",
+            EapMethod::Ttls => "TTLS",
+            EapMethod::Tls => "TLS",
+            EapMethod::Pwd => "PWD",
+            EapMethod::Leap => "LEAP",
+            EapMethod::Fast => "FAST",
+            EapMethod::Sim => "SIM",
+            EapMethod::Aka => "AKA",
+            EapMethod::AkaPrime => "AKA_PRIME",
+        }
+    }
 }
 
-// Note: The actual implementations for get_known_networks (and fetch_password_for_ssid for macOS/Windows)
-// are located in their respective OS-specific files (e.g., macos.rs, windows.rs, linux.rs).
-// The pub use statements at the top of this file make them available under this module.
+/// Inner (phase 2) authentication method of a tunneled EAP method (the `PH2:` field of the QR code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase2Method {
+    Pap,
+    Chap,
+    Mschap,
+    Mschapv2,
+    Gtc,
+}
+
+impl Phase2Method {
+    /// Parses a phase 2 method name. Prefixes such as "auth=" (wpa_supplicant) are ignored.
+    pub fn from_name(name: &str) -> Option<Phase2Method> {
+        let normalized = name.trim().to_lowercase();
+        let normalized = normalized.trim_start_matches("auth=").trim_start_matches("autheap=");
+        match normalized {
+            "pap" => Some(Phase2Method::Pap),
+            "chap" => Some(Phase2Method::Chap),
+            "mschap" => Some(Phase2Method::Mschap),
+            "mschapv2" => Some(Phase2Method::Mschapv2),
+            "gtc" => Some(Phase2Method::Gtc),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used for the `PH2:` field of the QR code.
+    pub fn qr_value(&self) -> &'static str {
+        match self {
+            Phase2Method::Pap => "PAP",
+            Phase2Method::Chap => "CHAP",
+            Phase2Method::Mschap => "MSCHAP",
+            Phase2Method::Mschapv2 => "MSCHAPV2",
+            Phase2Method::Gtc => "GTC",
+        }
+    }
+}
+
+/// 802.1X credentials of a WPA2/WPA3-Enterprise network.
+/// The user's password is kept in `WifiNetwork::password` like for personal networks.
+#[derive(Debug, Clone)]
+pub struct EnterpriseCredentials {
+    pub eap_method: EapMethod,
+    pub phase2_method: Option<Phase2Method>,
+    pub identity: Option<String>,
+    pub anonymous_identity: Option<String>,
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::wpa_supplicant::parse_string_value;
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+// Bits of the AllowedKeyMgmt bitset (WifiConfiguration.KeyMgmt).
+const KEY_MGMT_NONE: usize = 0;
+const KEY_MGMT_WPA_PSK: usize = 1;
+const KEY_MGMT_WPA_EAP: usize = 2;
+const KEY_MGMT_IEEE8021X: usize = 3;
+const KEY_MGMT_WPA2_PSK: usize = 4;
+const KEY_MGMT_FT_PSK: usize = 6;
+const KEY_MGMT_FT_EAP: usize = 7;
+const KEY_MGMT_SAE: usize = 8;
+const KEY_MGMT_OWE: usize = 9;
+const KEY_MGMT_SUITE_B_192: usize = 10;
+const KEY_MGMT_WPA_PSK_SHA256: usize = 11;
+const KEY_MGMT_WPA_EAP_SHA256: usize = 12;
+
+// Returns the value child (<string>, <int>, <byte-array>, ...) with the given name attribute.
+fn value_node<'a, 'input>(parent: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    parent.children().find(|n| n.is_element() && n.attribute("name") == Some(name))
+}
+
+// Returns the text of a <string> value, if it is not empty.
+fn string_value(parent: Node, name: &str) -> Option<String> {
+    value_node(parent, name).and_then(|n| n.text()).filter(|text| !text.is_empty()).map(str::to_string)
+}
+
+// Returns the value attribute of an <int> or <boolean> value.
+fn attribute_value<'a>(parent: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    value_node(parent, name).and_then(|n| n.attribute("value"))
+}
+
+// Decodes a <byte-array> bitset (java.util.BitSet.toByteArray, least significant bit first) into its set bits.
+fn parse_bitset(parent: Node, name: &str) -> Vec<usize> {
+    let bytes = value_node(parent, name).and_then(|n| n.text()).and_then(|text| hex::decode(text.trim()).ok()).unwrap_or_default();
+    (0..bytes.len() * 8).filter(|bit| bytes[bit / 8] & (1 << (bit % 8)) != 0).collect()
+}
+
+// Maps the AllowedKeyMgmt bits of a configuration to a security type.
+fn parse_key_mgmt(key_mgmt: &[usize], has_wep_key: bool) -> Option<SecurityType> {
+    let has = |bit: usize| key_mgmt.contains(&bit);
+    let has_psk = [KEY_MGMT_WPA_PSK, KEY_MGMT_WPA2_PSK, KEY_MGMT_FT_PSK, KEY_MGMT_WPA_PSK_SHA256].iter().any(|&bit| has(bit));
+    let has_eap = [KEY_MGMT_WPA_EAP, KEY_MGMT_IEEE8021X, KEY_MGMT_FT_EAP, KEY_MGMT_SUITE_B_192, KEY_MGMT_WPA_EAP_SHA256]
+        .iter()
+        .any(|&bit| has(bit));
+
+    if has_psk && has(KEY_MGMT_SAE) {
+        Some(SecurityType::Wpa2Wpa3Transition)
+    } else if has(KEY_MGMT_SAE) {
+        Some(SecurityType::Wpa3Sae)
+    } else if has_psk {
+        Some(SecurityType::WpaPersonal)
+    } else if has_eap {
+        Some(SecurityType::Enterprise)
+    } else if has(KEY_MGMT_OWE) {
+        Some(SecurityType::Owe)
+    } else if has(KEY_MGMT_NONE) || key_mgmt.is_empty() {
+        // Static WEP networks use KeyMgmt.NONE with WEP keys.
+        if has_wep_key {
+            Some(SecurityType::Wep)
+        } else {
+            Some(SecurityType::Open)
+        }
+    } else {
+        None
+    }
+}
+
+// Builds the 802.1X credentials of a <WifiEnterpriseConfiguration> element.
+// Returns the credentials and the user's password, or None for unsupported EAP methods.
+fn parse_enterprise_configuration(config: Node) -> Option<(EnterpriseCredentials, Option<String>)> {
+    // WifiEnterpriseConfig.Eap and WifiEnterpriseConfig.Phase2 constants.
+    let eap_method = match attribute_value(config, "EapMethod")? {
+        "0" => EapMethod::Peap,
+        "1" => EapMethod::Tls,
+        "2" => EapMethod::Ttls,
+        "3" => EapMethod::Pwd,
+        "4" => EapMethod::Sim,
+        "5" => EapMethod::Aka,
+        "6" => EapMethod::AkaPrime,
+        _ => return None,
+    };
+    let phase2_method = match attribute_value(config, "Phase2Method") {
+        Some("1") => Some(Phase2Method::Pap),
+        Some("2") => Some(Phase2Method::Mschap),
+        Some("3") => Some(Phase2Method::Mschapv2),
+        Some("4") => Some(Phase2Method::Gtc),
+        _ => None,
+    };
+
+    let credentials = EnterpriseCredentials {
+        eap_method,
+        phase2_method,
+        identity: string_value(config, "Identity"),
+        anonymous_identity: string_value(config, "AnonIdentity"),
+    };
+    Some((credentials, string_value(config, "Password")))
+}
+
+// Converts one <Network> element.
+fn parse_network(network: Node) -> Option<WifiNetwork> {
+    let config = network.children().find(|n| n.has_tag_name("WifiConfiguration"))?;
+    // SSIDs are stored quoted ("Home"), or as unquoted hex bytes if they are not valid UTF-8.
+    let ssid = string_value(config, "SSID").and_then(|ssid| parse_string_value(&ssid)).filter(|ssid| !ssid.is_empty())?;
+
+    let wep_index = attribute_value(config, "WEPTxKeyIndex").and_then(|index| index.parse::<usize>().ok()).unwrap_or(0);
+    let wep_key = value_node(config, "WEPKeys")
+        .and_then(|keys| keys.children().filter(|n| n.has_tag_name("item")).nth(wep_index))
+        .and_then(|item| item.attribute("value"))
+        .filter(|key| !key.is_empty())
+        .map(|key| key.trim_matches('"').to_string()); // "ASCII" or unquoted hex key.
+    let security_type = parse_key_mgmt(&parse_bitset(config, "AllowedKeyMgmt"), wep_key.is_some());
+
+    // Unquoted PreSharedKey values are raw 64-hex PSKs, which cannot be converted back to the passphrase.
+    let passphrase = string_value(config, "PreSharedKey")
+        .and_then(|psk| psk.strip_prefix('"').and_then(|psk| psk.strip_suffix('"')).map(str::to_string));
+    let hidden = attribute_value(config, "HiddenSSID") == Some("true");
+
+    let (password, enterprise) = match security_type {
+        Some(SecurityType::Enterprise) => network
+            .children()
+            .find(|n| n.has_tag_name("WifiEnterpriseConfiguration"))
+            .and_then(parse_enterprise_configuration)
+            .map_or((None, None), |(credentials, password)| (password, Some(credentials))),
+        Some(SecurityType::Wep) => (wep_key, None),
+        _ => (passphrase, None),
+    };
+    Some(WifiNetwork::new(ssid, password, security_type, hidden, enterprise))
+}
+
+/// Parses the saved networks of an Android WifiConfigStore.xml.
+pub fn parse_config_store(xml: &str) -> Result<Vec<WifiNetwork>, String> {
+    let document = Document::parse(xml).map_err(|e| format!("Invalid WifiConfigStore XML: {}", e))?;
+    let root = document.root_element();
+    if !root.has_tag_name("WifiConfigStoreData") {
+        return Err(format!("Expected a WifiConfigStoreData document, found <{}>.", root.tag_name().name()));
+    }
+    // Networks may be nested in a <NetworkList> (or a <NetworkList> per user/shared store).
+    Ok(root.descendants().filter(|n| n.has_tag_name("Network")).filter_map(parse_network).collect())
+}
+
+/// Reads the saved networks of an Android WifiConfigStore.xml file.
+pub fn read_config_store(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read WifiConfigStore '{}': {}", path.display(), e))?;
+    parse_config_store(&content).map_err(|e| format!("{} ({})", e.trim_end_matches('.'), path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_config_store_parses_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/android/WifiConfigStore.xml");
+        let networks = read_config_store(&path).unwrap();
+        assert_eq!(networks.len(), 5);
+
+        assert_eq!(networks[0].ssid, "Home Net");
+        assert_eq!(networks[0].password.as_deref(), Some("correct horse"));
+        assert_eq!(networks[0].security_type, Some(SecurityType::WpaPersonal));
+        assert!(!networks[0].hidden);
+
+        assert_eq!(networks[1].ssid, "Lab");
+        assert_eq!(networks[1].security_type, Some(SecurityType::Wpa3Sae));
+        assert!(networks[1].hidden);
+
+        assert_eq!(networks[2].ssid, "Old");
+        assert_eq!(networks[2].security_type, Some(SecurityType::Wep));
+        assert_eq!(networks[2].password.as_deref(), Some("abcde"));
+
+        assert_eq!(networks[3].ssid, "Café");
+        assert_eq!(networks[3].security_type, Some(SecurityType::Open));
+
+        let credentials = networks[4].enterprise.as_ref().unwrap();
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(networks[4].password.as_deref(), Some("s3cret"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use plist::{Dictionary, Value};
+
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+/// Payload type of Wi-Fi payloads in configuration profiles.
+const WIFI_PAYLOAD_TYPE: &str = "com.apple.wifi.managed";
+
+/// Maps an Apple security type name to a security type.
+/// Accepts the EncryptionType of Wi-Fi payloads ("WPA2", "WPA3", "WEP", "None", ...) as well as the
+/// names shown by macOS ("WPA2 Personal", "WPA2/WPA3 Personal", "WPA3 Enterprise", ...).
+/// `has_eap` marks payloads with EAP settings, whose WPA encryption types are enterprise networks.
+pub fn security_type_from_name(name: &str, has_eap: bool) -> Option<SecurityType> {
+    let normalized = name.trim().to_uppercase();
+    if normalized.contains("ENTERPRISE") || (has_eap && normalized.contains("WPA")) || (has_eap && normalized == "ANY") {
+        return Some(SecurityType::Enterprise);
+    }
+    match normalized.as_str() {
+        "" | "NONE" | "OPEN" => Some(SecurityType::Open),
+        "OWE" | "ENHANCED OPEN" => Some(SecurityType::Owe),
+        "WEP" | "DYNAMIC WEP" => Some(SecurityType::Wep),
+        // "Any" lets the device pick any personal security type; WPA2 covers most networks.
+        "ANY" | "WPA" | "WPA2" | "WPA PERSONAL" | "WPA2 PERSONAL" | "WPA/WPA2 PERSONAL" => Some(SecurityType::WpaPersonal),
+        "WPA3" | "WPA3 PERSONAL" => Some(SecurityType::Wpa3Sae),
+        "WPA2/WPA3" | "WPA2/WPA3 PERSONAL" => Some(SecurityType::Wpa2Wpa3Transition),
+        _ => None,
+    }
+}
+
+// Builds the 802.1X credentials of an EAPClientConfiguration dictionary.
+// Returns the credentials and the user's password, or None for unsupported EAP methods.
+fn parse_eap_configuration(eap: &Dictionary) -> Option<(EnterpriseCredentials, Option<String>)> {
+    let string = |key: &str| eap.get(key).and_then(Value::as_string).filter(|value| !value.is_empty()).map(str::to_string);
+    let eap_method = eap
+        .get("AcceptEAPTypes")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(Value::as_unsigned_integer)
+        .find_map(EapMethod::from_type_number)?;
+    let phase2_method = match eap_method {
+        EapMethod::Ttls => string("TTLSInnerAuthentication").and_then(|name| Phase2Method::from_name(&name)),
+        _ => None,
+    };
+
+    let credentials = EnterpriseCredentials {
+        eap_method,
+        phase2_method,
+        identity: string("UserName"),
+        anonymous_identity: string("OuterIdentity"),
+    };
+    Some((credentials, string("UserPassword")))
+}
+
+// Converts one Wi-Fi payload (or a bare Wi-Fi dictionary) to a network.
+fn parse_wifi_payload(payload: &Dictionary) -> Option<WifiNetwork> {
+    let ssid = payload.get("SSID_STR").and_then(Value::as_string).filter(|ssid| !ssid.is_empty())?.to_string();
+    let eap = payload.get("EAPClientConfiguration").and_then(Value::as_dictionary);
+    let encryption_type = payload.get("EncryptionType").and_then(Value::as_string).unwrap_or("Any");
+    let security_type = security_type_from_name(encryption_type, eap.is_some());
+    let password = payload.get("Password").and_then(Value::as_string).filter(|p| !p.is_empty()).map(str::to_string);
+    let hidden = payload.get("HIDDEN_NETWORK").and_then(Value::as_boolean).unwrap_or(false);
+
+    let (password, enterprise) = match security_type {
+        Some(SecurityType::Enterprise) => eap
+            .and_then(parse_eap_configuration)
+            .map_or((None, None), |(credentials, eap_password)| (eap_password, Some(credentials))),
+        _ => (password, None),
+    };
+    Some(WifiNetwork::new(ssid, password, security_type, hidden, enterprise))
+}
+
+// Collects the Wi-Fi payloads of a profile, including payloads nested in PayloadContent arrays.
+fn collect_wifi_payloads(value: &Value, networks: &mut Vec<WifiNetwork>) {
+    match value {
+        Value::Dictionary(dict) => {
+            let payload_type = dict.get("PayloadType").and_then(Value::as_string);
+            // Wi-Fi plists without payload metadata describe a single network.
+            if payload_type == Some(WIFI_PAYLOAD_TYPE) || (payload_type.is_none() && dict.contains_key("SSID_STR")) {
+                networks.extend(parse_wifi_payload(dict));
+            } else if let Some(content) = dict.get("PayloadContent") {
+                collect_wifi_payloads(content, networks);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_wifi_payloads(item, networks)),
+        _ => {}
+    }
+}
+
+/// Parses the Wi-Fi payloads of an unsigned configuration profile (.mobileconfig) or a Wi-Fi plist.
+/// Both XML and binary property lists are supported.
+pub fn parse_profile(content: &[u8]) -> Result<Vec<WifiNetwork>, String> {
+    let value = Value::from_reader(Cursor::new(content)).map_err(|e| {
+        // Signed profiles are CMS containers that embed the XML property list.
+        if content.windows(5).any(|window| window == b"<?xml") {
+            "Signed configuration profiles are not supported. Extract the profile with 'security cms -D -i <FILE>' first.".to_string()
+        } else {
+            format!("Invalid property list: {}", e)
+        }
+    })?;
+    let mut networks = Vec::new();
+    collect_wifi_payloads(&value, &mut networks);
+    Ok(networks)
+}
+
+/// Reads the Wi-Fi networks of a configuration profile or Wi-Fi plist file.
+pub fn read_profile(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let content = fs::read(path).map_err(|e| format!("Failed to read profile '{}': {}", path.display(), e))?;
+    parse_profile(&content).map_err(|e| format!("{} ({})", e.trim_end_matches('.'), path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_profile_parses_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/apple/wifi.mobileconfig");
+        let networks = read_profile(&path).unwrap();
+        assert_eq!(networks.len(), 3); // The VPN payload is skipped.
+
+        assert_eq!(networks[0].ssid, "Office");
+        assert_eq!(networks[0].password.as_deref(), Some("correct horse"));
+        assert_eq!(networks[0].security_type, Some(SecurityType::WpaPersonal));
+        assert!(networks[0].hidden);
+
+        assert_eq!(networks[1].ssid, "Lab");
+        assert_eq!(networks[1].security_type, Some(SecurityType::Wpa3Sae));
+
+        let credentials = networks[2].enterprise.as_ref().unwrap();
+        assert_eq!(networks[2].security_type, Some(SecurityType::Enterprise));
+        assert_eq!(credentials.eap_method, EapMethod::Ttls);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(credentials.anonymous_identity.as_deref(), Some("anonymous@example.com"));
+        assert_eq!(networks[2].password.as_deref(), Some("s3cret"));
+    }
+
+    #[test]
+    fn security_type_from_name_handles_macos_names() {
+        assert_eq!(security_type_from_name("WPA2 Personal", false), Some(SecurityType::WpaPersonal));
+        assert_eq!(security_type_from_name("WPA2/WPA3 Personal", false), Some(SecurityType::Wpa2Wpa3Transition));
+        assert_eq!(security_type_from_name("WPA3 Enterprise", false), Some(SecurityType::Enterprise));
+        assert_eq!(security_type_from_name("WPA2", true), Some(SecurityType::Enterprise));
+        assert_eq!(security_type_from_name("None", false), Some(SecurityType::Open));
+    }
+}
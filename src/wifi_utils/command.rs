@@ -0,0 +1,119 @@
+use std::process::Command;
+
+/// Captured output of an external command.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub status: i32, // Exit code; -1 if the command was terminated by a signal.
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// Returns true if the command exited with code 0.
+    pub fn success(&self) -> bool {
+        self.status == 0
+    }
+}
+
+/// Executes the external commands of the backends (nmcli, netsh, networksetup, security).
+/// Tests replace the system runner with recorded output, so the parsers can be tested on any OS.
+pub trait CommandRunner {
+    /// Runs the program and returns its output. Fails if the program cannot be executed, e.g. if it is not installed.
+    fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String>;
+}
+
+/// Runs commands on this system.
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String> {
+        let output = Command::new(program).args(args).output().map_err(|e| e.to_string())?;
+        Ok(CommandOutput {
+            status: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub use replay::ReplayRunner;
+
+#[cfg(test)]
+mod replay {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::Path;
+
+    use serde::Deserialize;
+
+    use super::{CommandOutput, CommandRunner};
+
+    // One recorded command. Arguments ending with '*' match any argument starting with the text before it;
+    // this is only meant for generated values such as temporary directories, all other arguments are recorded exactly.
+    #[derive(Deserialize)]
+    struct Recording {
+        program: String,
+        args: Vec<String>,
+        #[serde(default)]
+        status: i32,
+        #[serde(default)]
+        stdout: String,
+        #[serde(default)]
+        stderr: String,
+        // Files the command creates in the directory of its folder= argument, like `netsh wlan export profile`.
+        #[serde(default)]
+        files: BTreeMap<String, String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Recordings {
+        command: Vec<Recording>,
+    }
+
+    /// Replays commands recorded in a fixture of tests/fixtures/commands.
+    /// Commands without a recording fail as if the program was not installed.
+    pub struct ReplayRunner {
+        recordings: Vec<Recording>,
+    }
+
+    impl ReplayRunner {
+        /// Loads tests/fixtures/commands/<name>.toml.
+        pub fn from_fixture(name: &str) -> ReplayRunner {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/commands").join(format!("{}.toml", name));
+            let content = fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+            let recordings: Recordings = toml::from_str(&content).unwrap_or_else(|e| panic!("Invalid {}: {}", path.display(), e));
+            ReplayRunner { recordings: recordings.command }
+        }
+    }
+
+    fn args_match(recorded: &[String], args: &[&str]) -> bool {
+        recorded.len() == args.len()
+            && recorded.iter().zip(args).all(|(recorded, arg)| match recorded.strip_suffix('*') {
+                Some(prefix) => arg.starts_with(prefix),
+                None => recorded == arg,
+            })
+    }
+
+    impl CommandRunner for ReplayRunner {
+        fn run(&self, program: &str, args: &[&str]) -> Result<CommandOutput, String> {
+            let recording = self
+                .recordings
+                .iter()
+                .find(|recording| recording.program == program && args_match(&recording.args, args))
+                .ok_or_else(|| format!("No such file or directory (no recording of '{} {}')", program, args.join(" ")))?;
+
+            if !recording.files.is_empty() {
+                let folder = args.iter().find_map(|arg| arg.strip_prefix("folder=")).expect("folder= argument for recorded files");
+                for (name, content) in &recording.files {
+                    fs::write(Path::new(folder).join(name), content).expect("write recorded file");
+                }
+            }
+            Ok(CommandOutput {
+                status: recording.status,
+                stdout: recording.stdout.clone(),
+                stderr: recording.stderr.clone(),
+            })
+        }
+    }
+}
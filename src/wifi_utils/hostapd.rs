@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::wpa_supplicant::parse_string_value;
+use super::{SecurityType, WifiNetwork};
+
+// Parameters that may follow a sae_password, e.g. "secret|mac=ff:ff:ff:ff:ff:ff|id=guest".
+const SAE_PASSWORD_PARAMETERS: [&str; 4] = ["|mac=", "|vlanid=", "|pk=", "|id="];
+
+// Maps the wpa and wpa_key_mgmt settings of a BSS to a security type.
+// hostapd defaults to WPA-PSK if WPA is enabled without wpa_key_mgmt.
+fn parse_key_mgmt(settings: &HashMap<String, String>) -> Option<SecurityType> {
+    let wpa_enabled = settings.get("wpa").map(|wpa| wpa != "0").unwrap_or(false);
+    if !wpa_enabled {
+        // Without WPA the BSS is open, unless static WEP keys or 802.1X (dynamic WEP) are configured.
+        if settings.get("ieee8021x").map(|value| value == "1").unwrap_or(false) {
+            return None;
+        }
+        if (0..4).any(|i| settings.contains_key(&format!("wep_key{}", i))) {
+            return Some(SecurityType::Wep);
+        }
+        return Some(SecurityType::Open);
+    }
+
+    let key_mgmt = settings.get("wpa_key_mgmt").map(|value| value.to_uppercase()).unwrap_or_else(|| "WPA-PSK".to_string());
+    let methods: Vec<&str> = key_mgmt.split_whitespace().collect();
+    let has = |name: &str| methods.contains(&name);
+
+    let has_psk = has("WPA-PSK") || has("WPA-PSK-SHA256") || has("FT-PSK");
+    let has_sae = has("SAE") || has("SAE-EXT-KEY") || has("FT-SAE") || has("FT-SAE-EXT-KEY");
+    if has_psk && has_sae {
+        Some(SecurityType::Wpa2Wpa3Transition)
+    } else if has_sae {
+        Some(SecurityType::Wpa3Sae)
+    } else if has_psk {
+        Some(SecurityType::WpaPersonal)
+    } else if has("OWE") {
+        Some(SecurityType::Owe)
+    } else {
+        // WPA-EAP: the EAP method and credentials are configured on the RADIUS server, not in hostapd.conf.
+        None
+    }
+}
+
+// Returns the password of a sae_password setting without its parameters.
+fn strip_sae_parameters(sae_password: &str) -> &str {
+    let end = SAE_PASSWORD_PARAMETERS
+        .iter()
+        .filter_map(|parameter| sae_password.find(parameter))
+        .min()
+        .unwrap_or(sae_password.len());
+    &sae_password[..end]
+}
+
+// Converts the settings of one BSS (the main interface or a bss= section).
+fn parse_bss(settings: &HashMap<String, String>) -> Option<WifiNetwork> {
+    // ssid is taken verbatim; ssid2 is quoted, P"escaped" or hex like the ssid of wpa_supplicant.
+    let ssid = settings
+        .get("ssid2")
+        .and_then(|value| parse_string_value(value))
+        .or_else(|| settings.get("ssid").cloned())
+        .filter(|ssid| !ssid.is_empty())?;
+    let security_type = parse_key_mgmt(settings);
+
+    let non_empty = |key: &str| settings.get(key).filter(|value| !value.is_empty()).cloned();
+    let password = match security_type {
+        Some(SecurityType::Wep) => {
+            // Quoted WEP keys are text, unquoted ones hex digits.
+            let index = settings.get("wep_default_key").and_then(|index| index.parse::<u8>().ok()).unwrap_or(0);
+            settings.get(&format!("wep_key{}", index)).map(|key| key.trim_matches('"').to_string())
+        }
+        // SAE uses sae_password if set and falls back to wpa_passphrase; transition networks share the passphrase.
+        Some(SecurityType::Wpa3Sae) => non_empty("sae_password")
+            .map(|password| strip_sae_parameters(&password).to_string())
+            .or_else(|| non_empty("wpa_passphrase")),
+        // Networks with only a raw wpa_psk have no passphrase that could be encoded.
+        Some(SecurityType::WpaPersonal) | Some(SecurityType::Wpa2Wpa3Transition) => non_empty("wpa_passphrase"),
+        _ => None,
+    };
+
+    // 1 sends an empty SSID in beacons, 2 clears the SSID bytes; both hide the network.
+    let hidden = settings.get("ignore_broadcast_ssid").map(|value| value == "1" || value == "2").unwrap_or(false);
+    Some(WifiNetwork::new(ssid, password.filter(|password| !password.is_empty()), security_type, hidden, None))
+}
+
+/// Parses a hostapd configuration. The main interface and each bss= section are returned as separate networks.
+pub fn parse_config(content: &str) -> Vec<WifiNetwork> {
+    let mut sections: Vec<HashMap<String, String>> = vec![HashMap::new()];
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key == "bss" {
+            sections.push(HashMap::new());
+            continue;
+        }
+        // Values are used verbatim (SSIDs and passphrases may contain leading spaces); only the line ending is removed.
+        let value = value.trim_end_matches('\r');
+        // Like hostapd, later values override earlier ones. sae_password is the exception: hostapd accepts several
+        // lines and any of them authenticates (e.g. one per client or VLAN), so the first one is encoded.
+        if let Some(settings) = sections.last_mut() {
+            if key == "sae_password" {
+                settings.entry(key.to_string()).or_insert_with(|| value.to_string());
+            } else {
+                settings.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    sections.iter().filter_map(parse_bss).collect()
+}
+
+/// Reads the networks of a hostapd configuration file.
+pub fn read_config(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read hostapd configuration '{}': {}", path.display(), e))?;
+    Ok(parse_config(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTI_BSS_CONFIG: &str = "\
+interface=wlan0
+driver=nl80211
+ssid=Office
+wpa=2
+wpa_key_mgmt=WPA-PSK SAE
+wpa_passphrase=correct horse
+sae_password=ignored for transition
+
+bss=wlan0_1
+ssid2=\"Lab\"
+wpa=2
+wpa_key_mgmt=SAE
+sae_password=s3cret|id=lab
+sae_password=second
+ignore_broadcast_ssid=1
+
+bss=wlan0_2
+ssid=Guest
+";
+
+    #[test]
+    fn parse_config_lists_each_bss() {
+        let networks = parse_config(MULTI_BSS_CONFIG);
+        assert_eq!(networks.len(), 3);
+
+        assert_eq!(networks[0].ssid, "Office");
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wpa2Wpa3Transition));
+        assert_eq!(networks[0].password.as_deref(), Some("correct horse"));
+        assert!(!networks[0].hidden);
+
+        assert_eq!(networks[1].ssid, "Lab");
+        assert_eq!(networks[1].security_type, Some(SecurityType::Wpa3Sae));
+        assert_eq!(networks[1].password.as_deref(), Some("s3cret"));
+        assert!(networks[1].hidden);
+
+        assert_eq!(networks[2].ssid, "Guest");
+        assert_eq!(networks[2].security_type, Some(SecurityType::Open));
+        assert_eq!(networks[2].password, None);
+    }
+
+    #[test]
+    fn parse_config_uses_last_value_of_repeated_keys() {
+        let networks = parse_config("ssid=Old\nssid=Office\nwpa=2\nwpa_passphrase=first\nwpa_passphrase=second\n");
+        assert_eq!(networks[0].ssid, "Office");
+        assert_eq!(networks[0].password.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn parse_config_uses_first_of_several_sae_passwords() {
+        let networks = parse_config("ssid=Lab\nwpa=2\nwpa_key_mgmt=SAE\nsae_password=first|mac=ff:ff:ff:ff:ff:ff\nsae_password=second\n");
+        assert_eq!(networks[0].password.as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn parse_config_detects_wep_and_enterprise() {
+        let networks = parse_config("ssid=Old\nwep_default_key=1\nwep_key1=\"abcde\"\nbss=wlan0_1\nssid=Corp\nwpa=2\nwpa_key_mgmt=WPA-EAP\n");
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wep));
+        assert_eq!(networks[0].password.as_deref(), Some("abcde"));
+        assert_eq!(networks[1].security_type, None);
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::HashMap;
+
+/// Parses an INI-style file (iwd profiles, NetworkManager keyfiles) into a map of "section.key" to value.
+/// Lines starting with '#' or ';' are comments. Values are trimmed but not unescaped.
+pub fn parse_sections(content: &str) -> HashMap<String, String> {
+    let mut settings = HashMap::new();
+    let mut section = String::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            settings.insert(format!("{}.{}", section, key.trim()), value.trim().to_string());
+        }
+    }
+    settings
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::ini;
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork, WifiSource};
+
+/// Directory where iwd stores its network profiles.
+const DEFAULT_IWD_ROOT: &str = "/var/lib/iwd";
+/// Environment variable overriding the profile directory, e.g. to read fixtures or a mounted system.
+const IWD_ROOT_ENV: &str = "QRLAN_IWD_ROOT";
+
+// Returns the profile directory, honoring the QRLAN_IWD_ROOT override.
+fn iwd_root() -> PathBuf {
+    std::env::var_os(IWD_ROOT_ENV).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_IWD_ROOT))
+}
+
+// Decodes the SSID from a profile file name (without extension).
+// iwd stores SSIDs with characters other than letters, digits, ' ', '-' and '_' as '=' followed by the hex-encoded bytes.
+fn decode_profile_name(name: &str) -> Option<String> {
+    match name.strip_prefix('=') {
+        Some(hex_ssid) => hex::decode(hex_ssid).ok().and_then(|bytes| String::from_utf8(bytes).ok()),
+        None => Some(name.to_string()),
+    }
+}
+
+// Builds the 802.1X credentials of an .8021x profile.
+// For tunneled methods (PEAP, TTLS) EAP-Identity is the outer (anonymous) identity and the
+// EAP-<method>-Phase2-* settings hold the user's credentials.
+fn parse_enterprise_profile(settings: &HashMap<String, String>) -> Option<(EnterpriseCredentials, Option<String>)> {
+    let get = |key: &str| settings.get(&format!("Security.{}", key)).filter(|value| !value.is_empty()).cloned();
+    let method_name = get("EAP-Method")?;
+    let eap_method = EapMethod::from_name(&method_name)?;
+    let phase2_key = |suffix: &str| get(&format!("EAP-{}-Phase2-{}", method_name.to_uppercase(), suffix));
+
+    let phase2_method = phase2_key("Method")
+        .and_then(|name| Phase2Method::from_name(name.trim_start_matches("Tunneled-").trim_start_matches("tunneled-")));
+    let (identity, anonymous_identity) = match phase2_key("Identity") {
+        Some(inner_identity) => (Some(inner_identity), get("EAP-Identity")),
+        None => (get("EAP-Identity"), None),
+    };
+    let password = phase2_key("Password").or_else(|| get("EAP-Password"));
+
+    Some((EnterpriseCredentials { eap_method, phase2_method, identity, anonymous_identity }, password))
+}
+
+// Reads one profile file. Returns None for files that are not Wi-Fi profiles.
+fn read_profile(path: &Path) -> Result<Option<WifiNetwork>, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if !["psk", "open", "8021x"].contains(&extension) {
+        return Ok(None);
+    }
+    let Some(ssid) = path.file_stem().and_then(|s| s.to_str()).and_then(decode_profile_name) else {
+        return Ok(None);
+    };
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read iwd profile '{}': {}", path.display(), e))?;
+    let settings = ini::parse_sections(&content);
+    let hidden = settings.get("Settings.Hidden").map(|value| value == "true").unwrap_or(false);
+
+    let network = match extension {
+        "open" => WifiNetwork { security_type: Some(SecurityType::Open), hidden, ..WifiNetwork::from_ssid(ssid) },
+        "psk" => WifiNetwork {
+            // Only the passphrase can be encoded; profiles with just the derived PreSharedKey have no usable password.
+            password: settings.get("Security.Passphrase").filter(|p| !p.is_empty()).cloned(),
+            security_type: Some(SecurityType::WpaPersonal),
+            hidden,
+            ..WifiNetwork::from_ssid(ssid)
+        },
+        _ => {
+            let (password, enterprise) = parse_enterprise_profile(&settings)
+                .map_or((None, None), |(credentials, password)| (password, Some(credentials)));
+            WifiNetwork::new(ssid, password, Some(SecurityType::Enterprise), hidden, enterprise)
+        }
+    };
+    Ok(Some(network))
+}
+
+/// Reads all Wi-Fi profiles from an iwd profile directory.
+pub fn read_profiles(root: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let entries = fs::read_dir(root).map_err(|e| match e.kind() {
+        ErrorKind::PermissionDenied => format!(
+            "Permission denied reading iwd profiles in '{}'. Run qrlan with sudo to read them.",
+            root.display()
+        ),
+        _ => format!("Failed to read iwd profile directory '{}': {}", root.display(), e),
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+    paths.sort();
+    let mut networks = Vec::new();
+    for path in paths {
+        if let Some(network) = read_profile(&path)? {
+            networks.push(network);
+        }
+    }
+    Ok(networks)
+}
+
+/// Returns true if the iwd profile directory exists.
+pub fn is_available() -> bool {
+    iwd_root().is_dir()
+}
+
+/// Returns the networks known to iwd, read from /var/lib/iwd (or $QRLAN_IWD_ROOT).
+pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
+    let networks = read_profiles(&iwd_root())?;
+    if networks.is_empty() {
+        println!("No Wi-Fi networks found in the iwd profiles. You can enter network details manually.");
+    }
+    Ok(networks)
+}
+
+/// Network profiles of iwd.
+pub struct IwdSource;
+
+impl WifiSource for IwdSource {
+    fn name(&self) -> &str {
+        "iwd"
+    }
+
+    fn is_available(&self) -> bool {
+        is_available()
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        get_known_networks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/iwd")
+    }
+
+    fn find<'a>(networks: &'a [WifiNetwork], ssid: &str) -> &'a WifiNetwork {
+        networks.iter().find(|network| network.ssid == ssid).expect("network from fixture")
+    }
+
+    #[test]
+    fn decode_profile_name_handles_hex_encoded_ssids() {
+        assert_eq!(decode_profile_name("Home Net").as_deref(), Some("Home Net"));
+        assert_eq!(decode_profile_name("=436166c3a9").as_deref(), Some("Café"));
+        assert_eq!(decode_profile_name("=zz"), None);
+    }
+
+    #[test]
+    fn read_profiles_parses_fixtures() {
+        let networks = read_profiles(&fixture_root()).unwrap();
+        assert_eq!(networks.len(), 4); // The unrelated file is skipped.
+
+        let home = find(&networks, "Home Net");
+        assert_eq!(home.password.as_deref(), Some("correct horse"));
+        assert_eq!(home.security_type, Some(SecurityType::WpaPersonal));
+        assert!(!home.hidden);
+
+        let cafe = find(&networks, "Café:Guest");
+        assert_eq!(cafe.security_type, Some(SecurityType::Open));
+        assert!(cafe.hidden);
+
+        let psk_only = find(&networks, "Legacy");
+        assert_eq!(psk_only.password, None);
+
+        let corp = find(&networks, "Corp");
+        let credentials = corp.enterprise.as_ref().unwrap();
+        assert_eq!(corp.security_type, Some(SecurityType::Enterprise));
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(credentials.anonymous_identity.as_deref(), Some("anonymous@example.com"));
+        assert_eq!(corp.password.as_deref(), Some("s3cret"));
+    }
+}
@@ -1,75 +1,143 @@
-use std::process::Command;
-use super::WifiNetwork;
+use std::collections::HashMap;
+use super::command::{CommandRunner, SystemRunner};
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork, WifiSource};
 
-pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
-    // Using nmcli to get saved Wi-Fi connections, their SSIDs, security, and PSKs (passwords).
-    // The command: nmcli -t -f GENERAL.NAME,802-11-WIRELESS.SSID,802-11-WIRELESS-SECURITY.KEY-MGMT,802-11-WIRELESS-SECURITY.PSK,TYPE connection show
-    // -t for terse, script-friendly output.
-    // -f specifies the fields to output.
-    //   GENERAL.NAME: The connection name (profile name).
-    //   802-11-WIRELESS.SSID: The actual SSID of the network.
-    //   802-11-WIRELESS-SECURITY.KEY-MGMT: Indicates security type (e.g., wpa-psk, wpa-eap, none).
-    //   802-11-WIRELESS-SECURITY.PSK: The pre-shared key (password), if applicable and accessible.
-    //   TYPE: The type of the connection (we are interested in '802-11-wireless').
-    // Note: Accessing PSKs might require specific permissions.
+// Maps NetworkManager's 802-11-wireless-security.key-mgmt values to a security type.
+// Open connections have no security setting at all (empty key-mgmt); "none" is static WEP.
+pub(super) fn parse_key_mgmt(key_mgmt: &str, has_wep_key: bool) -> Option<SecurityType> {
+    match key_mgmt {
+        "" if has_wep_key => Some(SecurityType::Wep),
+        "" => Some(SecurityType::Open),
+        "none" => Some(SecurityType::Wep),
+        "owe" => Some(SecurityType::Owe), // Wi-Fi Enhanced Open (Opportunistic Wireless Encryption).
+        "wpa-psk" => Some(SecurityType::WpaPersonal),
+        "sae" => Some(SecurityType::Wpa3Sae), // WPA3-Personal (SAE).
+        "wpa-eap" | "wpa-eap-suite-b-192" => Some(SecurityType::Enterprise),
+        _ => None, // Unknown or unsupported security type by this application.
+    }
+}
 
-    let output = Command::new("nmcli")
-        .args(&[
-            "-t", // Terse output for easy parsing.
-            "-f", "GENERAL.NAME,802-11-WIRELESS.SSID,802-11-WIRELESS-SECURITY.KEY-MGMT,802-11-WIRELESS-SECURITY.PSK,TYPE", // Fields to retrieve.
-            "connection",
-            "show", // Show all configured connections.
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute nmcli. Is NetworkManager installed and running? Error: {}", e))?;
+// Builds the 802.1X credentials from nmcli's 802-1x settings.
+// Returns None if no supported EAP method is configured. nmcli lists multiple methods separated by ','.
+pub(super) fn parse_enterprise_credentials(eap: &str, phase2_auth: &str, identity: &str, anonymous_identity: &str) -> Option<EnterpriseCredentials> {
+    let eap_method = eap.split(',').find_map(EapMethod::from_name)?;
+    let non_empty = |value: &str| if value.is_empty() { None } else { Some(value.to_string()) };
 
-    if !output.status.success() {
-        let error_message = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("nmcli command failed with status {}: {}.", output.status, error_message));
+    Some(EnterpriseCredentials {
+        eap_method,
+        phase2_method: Phase2Method::from_name(phase2_auth),
+        identity: non_empty(identity),
+        anonymous_identity: non_empty(anonymous_identity),
+    })
+}
+
+// Splits a line of nmcli's terse (-t) output into its values.
+// nmcli escapes ':' and '\' inside values with a backslash, so only unescaped colons separate fields.
+fn split_terse_line(line: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            ':' => values.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
     }
+    values.push(current);
+    values
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+// Decodes a byte array as printed by nmcli ("0x4775657374"). Returns None for other values and invalid UTF-8.
+fn decode_byte_array(value: &str) -> Option<String> {
+    let hex_digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    hex::decode(hex_digits).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+// Parses the terse output of the `nmcli connection show` call in get_known_networks.
+fn parse_connection_list(output: &str) -> Vec<WifiNetwork> {
     let mut networks = Vec::new();
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split(':').collect();
-        // Expected format after splitting by ':':
-        // [Connection Name, SSID (Hex), Key Management, PSK, Connection Type]
-        // We filter for wireless connections by checking if the TYPE (parts[4]) is "802-11-wireless".
-        if parts.len() >= 5 && parts[4] == "802-11-wireless" {
+    for line in output.lines() {
+        let parts = split_terse_line(line);
+        // Expected format after splitting by unescaped ':':
+        // [Connection Name, SSID (text or byte array), Key Management, PSK, Hidden,
+        //  EAP, Phase 2 Auth, Identity, Anonymous Identity, 802.1X Password, Connection Type]
+        // We filter for wireless connections by checking if the TYPE (parts[10]) is "802-11-wireless".
+        if parts.len() >= 11 && parts[10] == "802-11-wireless" {
             let con_name = parts[0].to_string();
             
-            let ssid_hex = parts[1];
-            // SSID from nmcli can be hex-encoded. Decode it to a readable string.
-            // If hex SSID is empty or decoding fails, fallback to the connection name.
-            let ssid = if ssid_hex.is_empty() {
+            let ssid_value = parts[1].as_str();
+            // nmcli prints SSIDs as text. Only byte arrays ("0x" followed by hex digits) are decoded,
+            // so textual SSIDs that happen to be valid hex (e.g. "CAFE") are kept as they are.
+            // If the SSID field is empty, fallback to the connection name.
+            let ssid = if ssid_value.is_empty() {
                 con_name // Fallback to connection name if SSID field is empty.
             } else {
-                match hex::decode(ssid_hex) {
-                    Ok(bytes) => String::from_utf8(bytes).unwrap_or_else(|_| con_name.clone()), // Use con_name if UTF-8 decoding fails.
-                    Err(_) => con_name, // Use con_name if hex decoding fails.
-                }
+                decode_byte_array(ssid_value).unwrap_or_else(|| ssid_value.to_string())
             };
 
-            let key_mgmt = parts[2]; // Security key management type.
-            let psk = parts[3];      // Pre-shared key (password).
+            let key_mgmt = parts[2].as_str(); // Security key management type.
+            let psk = parts[3].as_str();      // Pre-shared key (password).
+            let hidden = parts[4] == "yes"; // Non-broadcast SSID.
 
-            let password = if psk.is_empty() { None } else { Some(psk.to_string()) };
-            
-            // Map nmcli's key management types to simplified types used by the application (WPA, WEP, nopass).
-            let security_type = match key_mgmt {
-                "wpa-psk" | "sae" /* WPA3-Personal (SAE) */ | "wpa-eap" => Some("WPA".to_string()), // Group WPA/WPA2/WPA3 under "WPA".
-                "wep-psk" | "wep-key" => Some("WEP".to_string()),
-                "none" | "owe" /* Wi-Fi Enhanced Open (Opportunistic Wireless Encryption) */ => Some("nopass".to_string()),
-                _ => None, // Unknown or unsupported security type by this application.
+            // Enterprise networks authenticate with the 802.1X password instead of a PSK.
+            let enterprise = if parse_key_mgmt(key_mgmt, false) == Some(SecurityType::Enterprise) {
+                parse_enterprise_credentials(&parts[5], &parts[6], &parts[7], &parts[8])
+            } else {
+                None
             };
+            let secret = if enterprise.is_some() { parts[9].as_str() } else { psk };
+            let password = if secret.is_empty() { None } else { Some(secret.to_string()) };
+            let security_type = parse_key_mgmt(key_mgmt, false);
             
             // Only add the network if an SSID was successfully determined.
             if !ssid.is_empty() {
-                 networks.push(WifiNetwork { ssid, password, security_type });
+                 networks.push(WifiNetwork::new(ssid, password, security_type, hidden, enterprise));
             }
         }
     }
+    networks
+}
+
+// Returns true if the nmcli command can be executed (NetworkManager is installed).
+fn is_nmcli_installed(runner: &dyn CommandRunner) -> bool {
+    runner.run("nmcli", &["--version"]).is_ok()
+}
+
+/// Returns the saved Wi-Fi connections of NetworkManager, read with nmcli.
+pub fn get_known_networks(runner: &dyn CommandRunner) -> Result<Vec<WifiNetwork>, String> {
+    // Using nmcli to get saved Wi-Fi connections, their SSIDs, security, and PSKs (passwords).
+    // The command: nmcli -t -f GENERAL.NAME,802-11-WIRELESS.SSID,802-11-WIRELESS-SECURITY.KEY-MGMT,802-11-WIRELESS-SECURITY.PSK,802-11-WIRELESS.HIDDEN,802-1X.EAP,802-1X.PHASE2-AUTH,802-1X.IDENTITY,802-1X.ANONYMOUS-IDENTITY,802-1X.PASSWORD,TYPE connection show
+    // -t for terse, script-friendly output.
+    // -f specifies the fields to output.
+    //   GENERAL.NAME: The connection name (profile name).
+    //   802-11-WIRELESS.SSID: The actual SSID of the network.
+    //   802-11-WIRELESS-SECURITY.KEY-MGMT: Indicates security type (e.g., wpa-psk, wpa-eap, none for WEP; empty if open).
+    //   802-11-WIRELESS-SECURITY.PSK: The pre-shared key (password), if applicable and accessible.
+    //   802-11-WIRELESS.HIDDEN: Whether the network is hidden (non-broadcast SSID), "yes" or "no".
+    //   802-1X.*: EAP method, phase 2 method, identities and password of enterprise (wpa-eap) networks.
+    //   TYPE: The type of the connection (we are interested in '802-11-wireless').
+    // Note: Accessing PSKs might require specific permissions.
+
+    let output = runner
+        .run("nmcli", &[
+            "-t", // Terse output for easy parsing.
+            "-f", "GENERAL.NAME,802-11-WIRELESS.SSID,802-11-WIRELESS-SECURITY.KEY-MGMT,802-11-WIRELESS-SECURITY.PSK,802-11-WIRELESS.HIDDEN,802-1X.EAP,802-1X.PHASE2-AUTH,802-1X.IDENTITY,802-1X.ANONYMOUS-IDENTITY,802-1X.PASSWORD,TYPE", // Fields to retrieve.
+            "connection",
+            "show", // Show all configured connections.
+        ])
+        .map_err(|e| format!("Failed to execute nmcli. Is NetworkManager installed and running? Error: {}", e))?;
+
+    if !output.success() {
+        return Err(format!("nmcli command failed with exit code {}: {}.", output.status, output.stderr.trim().trim_end_matches('.')));
+    }
+
+    let networks = parse_connection_list(&output.stdout);
     
     if networks.is_empty() {
         // Inform user if no networks were found or details couldn't be retrieved.
@@ -79,5 +147,410 @@ pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
     Ok(networks)
 }
 
+// Runs nmcli with the given arguments and returns its stdout.
+// Authorization failures (polkit denials) get a dedicated message, as they are common for secrets.
+fn run_nmcli(runner: &dyn CommandRunner, args: &[&str]) -> Result<String, String> {
+    let output = runner
+        .run("nmcli", args)
+        .map_err(|e| format!("Failed to execute nmcli. Is NetworkManager installed and running? Error: {}", e))?;
+
+    if !output.success() {
+        let error_message = &output.stderr;
+        let lowercase = error_message.to_lowercase();
+        if lowercase.contains("not authorized") || lowercase.contains("insufficient privileges") || lowercase.contains("permission denied") {
+            return Err(format!(
+                "NetworkManager denied access (polkit): {}. Run qrlan as a user allowed to read the connection's secrets, e.g. with sudo",
+                error_message.trim()
+            ));
+        }
+        return Err(format!("nmcli command failed with exit code {}: {}", output.status, error_message.trim()));
+    }
+    Ok(output.stdout)
+}
+
+// Parses the terse output of `nmcli -f ACTIVE,SSID device wifi list` and returns the SSID of the active access point.
+fn parse_active_ssid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(split_terse_line)
+        .find(|parts| parts.len() >= 2 && parts[0] == "yes" && !parts[1].is_empty())
+        .map(|parts| parts[1].clone())
+}
+
+/// Returns the SSID of the access point NetworkManager is connected to, from the cached scan results.
+pub fn current_ssid(runner: &dyn CommandRunner) -> Result<Option<String>, String> {
+    let output = run_nmcli(runner, &["-t", "-f", "ACTIVE,SSID", "device", "wifi", "list", "--rescan", "no"])?;
+    Ok(parse_active_ssid(&output))
+}
+
+// Returns the names of all Wi-Fi connection profiles.
+fn wifi_connection_names(runner: &dyn CommandRunner) -> Result<Vec<String>, String> {
+    let output = run_nmcli(runner, &["-t", "-f", "NAME,TYPE", "connection", "show"])?;
+    Ok(output
+        .lines()
+        .map(split_terse_line)
+        .filter(|parts| parts.len() >= 2 && parts[1] == "802-11-wireless")
+        .map(|parts| parts[0].clone())
+        .collect())
+}
+
+// Reads settings of one connection profile. With show_secrets, nmcli asks NetworkManager for the stored secrets.
+// The terse output of a single connection has one "setting:value" line per field.
+fn connection_settings(runner: &dyn CommandRunner, name: &str, fields: &str, show_secrets: bool) -> Result<HashMap<String, String>, String> {
+    let mut args = vec!["-t", "-f", fields];
+    if show_secrets {
+        args.insert(0, "--show-secrets");
+    }
+    args.extend(["connection", "show", "id", name]);
+
+    let output = run_nmcli(runner, &args)?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let parts = split_terse_line(line);
+            let (setting, value) = parts.split_first()?;
+            Some((setting.to_lowercase(), value.join(":")))
+        })
+        .collect())
+}
+
+/// Fetches the password of a saved network by querying its NetworkManager connection with `--show-secrets`.
+/// Connections named after the SSID are checked first. Returns Ok(None) if no connection or secret is found.
+pub fn fetch_password_for_ssid(runner: &dyn CommandRunner, ssid: &str) -> Result<Option<String>, String> {
+    let mut names = wifi_connection_names(runner)?;
+    names.sort_by_key(|name| name != ssid);
+
+    for name in names {
+        let settings = connection_settings(runner, &name, "802-11-wireless.ssid,802-11-wireless-security.key-mgmt", false)?;
+        if settings.get("802-11-wireless.ssid").map(String::as_str) != Some(ssid) {
+            continue;
+        }
+
+        // The secret setting depends on the security type of the connection.
+        let secret_field = match settings.get("802-11-wireless-security.key-mgmt").map(String::as_str) {
+            Some("wpa-psk") | Some("sae") => "802-11-wireless-security.psk",
+            Some("none") => "802-11-wireless-security.wep-key0", // Static WEP.
+            Some("wpa-eap") | Some("wpa-eap-suite-b-192") => "802-1x.password",
+            _ => return Ok(None), // Open network or no security settings.
+        };
+        let secrets = connection_settings(runner, &name, secret_field, true)?;
+        return Ok(secrets.get(secret_field).filter(|secret| !secret.is_empty()).cloned());
+    }
+    Ok(None)
+}
+
+/// NetworkManager connections, read with nmcli.
+pub struct NmcliSource {
+    runner: Box<dyn CommandRunner>,
+}
+
+impl NmcliSource {
+    pub fn new(runner: Box<dyn CommandRunner>) -> NmcliSource {
+        NmcliSource { runner }
+    }
+}
+
+impl WifiSource for NmcliSource {
+    fn name(&self) -> &str {
+        "nmcli"
+    }
+
+    // Skipped if QRLAN_NM_ROOT points the keyfile source at the connections of another system.
+    fn is_available(&self) -> bool {
+        !super::nm_keyfile::is_root_overridden() && is_nmcli_installed(self.runner.as_ref())
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        get_known_networks(self.runner.as_ref())
+    }
+
+    fn fetch_password(&self, ssid: &str) -> Result<Option<String>, String> {
+        fetch_password_for_ssid(self.runner.as_ref(), ssid)
+    }
+
+    fn current_ssid(&self) -> Result<Option<String>, String> {
+        current_ssid(self.runner.as_ref())
+    }
+}
+
+// Maps an EAP method to its name in nmcli's 802-1x.eap setting.
+fn eap_method_nm_name(eap_method: EapMethod) -> &'static str {
+    match eap_method {
+        EapMethod::Peap => "peap",
+        EapMethod::Ttls => "ttls",
+        EapMethod::Tls => "tls",
+        EapMethod::Pwd => "pwd",
+        EapMethod::Leap => "leap",
+        EapMethod::Fast => "fast",
+        EapMethod::Sim => "sim",
+        EapMethod::Aka => "aka",
+        EapMethod::AkaPrime => "aka'",
+    }
+}
+
+/// Returns the arguments of the `nmcli connection add` command that creates a connection profile for the network.
+/// The connection is named after the SSID.
+pub fn connection_add_args(network: &WifiNetwork) -> Result<Vec<String>, String> {
+    let security_type = network
+        .security_type
+        .ok_or(format!("The security type of '{}' is unknown.", network.ssid))?;
+    let password = match network.password.as_deref() {
+        Some(password) => password,
+        None if security_type.requires_password() && security_type != SecurityType::Enterprise => {
+            return Err(format!("The QR code contains no password for '{}'.", network.ssid));
+        }
+        None => "",
+    };
+
+    let mut args: Vec<String> = ["connection", "add", "type", "wifi", "con-name", &network.ssid, "ssid", &network.ssid]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let mut set = |setting: &str, value: &str| {
+        args.push(setting.to_string());
+        args.push(value.to_string());
+    };
+    if network.hidden {
+        set("802-11-wireless.hidden", "yes");
+    }
+
+    match security_type {
+        SecurityType::Open => {}
+        SecurityType::Owe => set("wifi-sec.key-mgmt", "owe"),
+        SecurityType::Wep => {
+            set("wifi-sec.key-mgmt", "none");
+            // 5/13 ASCII or 10/26 hex characters are raw keys, anything else is a passphrase.
+            let is_hex = password.chars().all(|c| c.is_ascii_hexdigit());
+            let is_key = match password.len() {
+                5 | 13 => password.is_ascii(),
+                10 | 26 => is_hex,
+                _ => false,
+            };
+            let key_type = if is_key { "key" } else { "phrase" };
+            set("wifi-sec.wep-key-type", key_type);
+            set("wifi-sec.wep-key0", password);
+        }
+        SecurityType::WpaPersonal | SecurityType::Wpa2Wpa3Transition => {
+            set("wifi-sec.key-mgmt", "wpa-psk");
+            set("wifi-sec.psk", password);
+        }
+        SecurityType::Wpa3Sae => {
+            set("wifi-sec.key-mgmt", "sae");
+            set("wifi-sec.psk", password);
+        }
+        SecurityType::Enterprise => {
+            let credentials = network
+                .enterprise
+                .as_ref()
+                .ok_or(format!("The QR code contains no 802.1X settings for '{}'.", network.ssid))?;
+            set("wifi-sec.key-mgmt", "wpa-eap");
+            set("802-1x.eap", eap_method_nm_name(credentials.eap_method));
+            if let Some(phase2) = credentials.phase2_method {
+                set("802-1x.phase2-auth", &phase2.qr_value().to_lowercase());
+            }
+            if let Some(ref identity) = credentials.identity {
+                set("802-1x.identity", identity);
+            }
+            if let Some(ref anonymous_identity) = credentials.anonymous_identity {
+                set("802-1x.anonymous-identity", anonymous_identity);
+            }
+            if !password.is_empty() {
+                set("802-1x.password", password);
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Creates a NetworkManager connection profile for the network with `nmcli connection add`.
+pub fn add_connection(network: &WifiNetwork) -> Result<(), String> {
+    let args = connection_add_args(network)?;
+    run_nmcli(&SystemRunner, &args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Ok(())
+}
+
 // Reminder: Add the 'hex' crate to Cargo.toml if not already present:
 // hex = "0.4"
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::command::ReplayRunner;
+
+    // Output of the nmcli call in get_known_networks, including values with escaped colons and backslashes.
+    const CONNECTION_SHOW: &str = include_str!("../../tests/fixtures/nmcli/connection_show.txt");
+
+    #[test]
+    fn split_terse_line_unescapes_colons_and_backslashes() {
+        assert_eq!(split_terse_line(r"a\:b:c\\:d"), vec!["a:b", r"c\", "d"]);
+        assert_eq!(split_terse_line("::"), vec!["", "", ""]);
+    }
+
+    #[test]
+    fn parse_connection_list_keeps_fields_with_colons_aligned() {
+        let networks = parse_connection_list(CONNECTION_SHOW);
+        assert_eq!(networks.len(), 6); // The ethernet connection is skipped.
+
+        assert_eq!(networks[0].ssid, "Office:2F");
+        assert_eq!(networks[0].password.as_deref(), Some("pa:ss:word"));
+        assert_eq!(networks[0].security_type, Some(SecurityType::WpaPersonal));
+
+        assert_eq!(networks[1].password.as_deref(), Some(r"C\:drive"));
+        assert_eq!(networks[1].security_type, Some(SecurityType::Wpa3Sae));
+        assert!(networks[1].hidden);
+
+        let credentials = networks[2].enterprise.as_ref().expect("enterprise credentials");
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(networks[2].password.as_deref(), Some("s3cr:t"));
+
+        assert_eq!(networks[3].ssid, "Guest"); // SSID printed as byte array.
+        assert_eq!(networks[3].security_type, Some(SecurityType::Open)); // No security setting.
+
+        assert_eq!(networks[4].ssid, "Legacy");
+        assert_eq!(networks[4].security_type, Some(SecurityType::Wep)); // key-mgmt "none".
+
+        assert_eq!(networks[5].ssid, "CAFE"); // Textual SSID consisting of hex digits.
+    }
+
+    #[test]
+    fn decode_byte_array_requires_prefix() {
+        assert_eq!(decode_byte_array("0x4775657374").as_deref(), Some("Guest"));
+        assert_eq!(decode_byte_array("CAFE"), None);
+        assert_eq!(decode_byte_array("0xFF"), None); // Not valid UTF-8.
+        assert_eq!(decode_byte_array("0xCAF"), None);
+    }
+
+    fn replay(fixture: &str) -> NmcliSource {
+        NmcliSource::new(Box::new(ReplayRunner::from_fixture(fixture)))
+    }
+
+    #[test]
+    fn known_networks_from_recorded_nmcli() {
+        let networks = replay("nmcli").known_networks().unwrap();
+        let ssids: Vec<&str> = networks.iter().map(|network| network.ssid.as_str()).collect();
+        assert_eq!(ssids, ["Office:2F", "Corp", "Guest"]);
+        assert_eq!(networks[0].password, None); // Secrets are not listed without --show-secrets.
+    }
+
+    #[test]
+    fn fetch_password_finds_connection_by_ssid() {
+        let source = replay("nmcli");
+        assert_eq!(source.fetch_password("Office:2F").unwrap().as_deref(), Some("pa:ss:word"));
+        assert_eq!(source.fetch_password("Guest").unwrap(), None); // Open network.
+        assert_eq!(source.fetch_password("Unknown").unwrap(), None);
+    }
+
+    #[test]
+    fn fetch_password_reports_polkit_denial() {
+        let error = replay("nmcli").fetch_password("Corp").unwrap_err();
+        assert!(error.starts_with("NetworkManager denied access (polkit): Error: Failed to get secrets"), "{}", error);
+    }
+
+    #[test]
+    fn current_ssid_of_active_access_point() {
+        assert_eq!(replay("nmcli").current_ssid().unwrap().as_deref(), Some("Office:2F"));
+        assert_eq!(parse_active_ssid("no:Home\nno:\n"), None);
+    }
+
+    fn network(ssid: &str, password: Option<&str>, security_type: SecurityType, hidden: bool) -> WifiNetwork {
+        WifiNetwork {
+            ssid: ssid.to_string(),
+            password: password.map(str::to_string),
+            security_type: Some(security_type),
+            hidden,
+            enterprise: None,
+        }
+    }
+
+    fn add_args(network: &WifiNetwork) -> Vec<String> {
+        connection_add_args(network).unwrap()
+    }
+
+    // The expected arguments for a network named "Net" with the given settings.
+    fn expected_args(settings: &[&str]) -> Vec<String> {
+        ["connection", "add", "type", "wifi", "con-name", "Net", "ssid", "Net"]
+            .iter()
+            .chain(settings)
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn connection_add_args_for_personal_networks() {
+        let expected = expected_args;
+        assert_eq!(add_args(&network("Net", None, SecurityType::Open, false)), expected(&[]));
+        assert_eq!(add_args(&network("Net", None, SecurityType::Owe, false)), expected(&["wifi-sec.key-mgmt", "owe"]));
+        assert_eq!(
+            add_args(&network("Net", Some("secret pw"), SecurityType::WpaPersonal, false)),
+            expected(&["wifi-sec.key-mgmt", "wpa-psk", "wifi-sec.psk", "secret pw"])
+        );
+        assert_eq!(
+            add_args(&network("Net", Some("secret"), SecurityType::Wpa2Wpa3Transition, false)),
+            expected(&["wifi-sec.key-mgmt", "wpa-psk", "wifi-sec.psk", "secret"])
+        );
+        assert_eq!(
+            add_args(&network("Net", Some("secret"), SecurityType::Wpa3Sae, true)),
+            expected(&["802-11-wireless.hidden", "yes", "wifi-sec.key-mgmt", "sae", "wifi-sec.psk", "secret"])
+        );
+    }
+
+    #[test]
+    fn connection_add_args_chooses_wep_key_type() {
+        let wep = |password: &str| add_args(&network("Net", Some(password), SecurityType::Wep, false));
+        let expected = |key_type: &str, key: &str| {
+            expected_args(&["wifi-sec.key-mgmt", "none", "wifi-sec.wep-key-type", key_type, "wifi-sec.wep-key0", key])
+        };
+        assert_eq!(wep("abcde"), expected("key", "abcde")); // 5 ASCII characters (WEP-40).
+        assert_eq!(wep("0102030405"), expected("key", "0102030405")); // 10 hex digits (WEP-40).
+        assert_eq!(wep("abcdefghijklm"), expected("key", "abcdefghijklm")); // 13 ASCII characters (WEP-104).
+        assert_eq!(wep("0102030405060708090a0b0c0d"), expected("key", "0102030405060708090a0b0c0d"));
+        assert_eq!(wep("my long passphrase"), expected("phrase", "my long passphrase"));
+        assert_eq!(wep("helloworld"), expected("phrase", "helloworld")); // 10 characters, but not hex.
+    }
+
+    #[test]
+    fn connection_add_args_for_enterprise_networks() {
+        let mut corp = network("Corp", Some("s3cret"), SecurityType::Enterprise, false);
+        assert!(connection_add_args(&corp).unwrap_err().contains("no 802.1X settings"));
+
+        corp.enterprise = Some(EnterpriseCredentials {
+            eap_method: EapMethod::Ttls,
+            phase2_method: Some(Phase2Method::Mschapv2),
+            identity: Some("alice".to_string()),
+            anonymous_identity: Some("anonymous@corp".to_string()),
+        });
+        assert_eq!(
+            add_args(&corp),
+            [
+                "connection", "add", "type", "wifi", "con-name", "Corp", "ssid", "Corp",
+                "wifi-sec.key-mgmt", "wpa-eap",
+                "802-1x.eap", "ttls",
+                "802-1x.phase2-auth", "mschapv2",
+                "802-1x.identity", "alice",
+                "802-1x.anonymous-identity", "anonymous@corp",
+                "802-1x.password", "s3cret",
+            ]
+        );
+
+        // The 802.1X password is optional, e.g. for TLS.
+        corp.password = None;
+        assert!(!add_args(&corp).contains(&"802-1x.password".to_string()));
+    }
+
+    #[test]
+    fn connection_add_args_requires_password_and_security_type() {
+        let error = connection_add_args(&network("Net", None, SecurityType::WpaPersonal, false)).unwrap_err();
+        assert_eq!(error, "The QR code contains no password for 'Net'.");
+        let error = connection_add_args(&WifiNetwork::from_ssid("Net".to_string())).unwrap_err();
+        assert_eq!(error, "The security type of 'Net' is unknown.");
+    }
+
+    #[test]
+    fn known_networks_fails_if_networkmanager_is_not_running() {
+        let source = replay("nmcli_not_running");
+        assert!(is_nmcli_installed(source.runner.as_ref()));
+        let error = source.known_networks().unwrap_err();
+        assert_eq!(error, "nmcli command failed with exit code 8: Error: NetworkManager is not running.");
+    }
+}
@@ -1,80 +1,179 @@
-use std::process::Command;
-use super::WifiNetwork;
-
-pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
-    // Attempt to find the active Wi-Fi interface device name (e.g., en0, en1).
-    let interfaces_output = Command::new("networksetup")
-        .arg("-listallhardwareports")
-        .output()
-        .map_err(|e| format!("Failed to execute 'networksetup -listallhardwareports': {}", e))?;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 
-    if !interfaces_output.status.success() {
-        // Error if the command to list hardware ports fails.
-        return Err(format!(
-            "'networksetup -listallhardwareports' command failed with status {}: {}.",
-            interfaces_output.status,
-            String::from_utf8_lossy(&interfaces_output.stderr)
-        ));
+use plist::Value;
+
+use super::apple_profile::security_type_from_name;
+use super::command::CommandRunner;
+use super::{SecurityType, WifiNetwork, WifiSource};
+
+// Known networks of macOS 13 and later, with their security type and hidden flag. Only readable by root.
+const KNOWN_NETWORKS_PATH: &str = "/Library/Preferences/com.apple.wifi.known-networks.plist";
+
+// Parses the known-networks plist. Its entries are named "wifi.network.ssid.<SSID>" and hold the SSID as data,
+// the security type as shown by macOS in SupportedSecurityTypes ("WPA2 Personal", ...) and a Hidden flag.
+fn parse_known_networks(content: &[u8]) -> Result<Vec<WifiNetwork>, String> {
+    let value = Value::from_reader(Cursor::new(content)).map_err(|e| format!("Invalid property list: {}", e))?;
+    let entries = value.as_dictionary().ok_or("The known networks are not a dictionary")?;
+
+    let mut networks = Vec::new();
+    for (key, entry) in entries {
+        let (Some(name), Some(entry)) = (key.strip_prefix("wifi.network.ssid."), entry.as_dictionary()) else {
+            continue;
+        };
+        let ssid = entry
+            .get("SSID")
+            .and_then(Value::as_data)
+            .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+            .unwrap_or_else(|| name.to_string());
+        let security_type = entry
+            .get("SupportedSecurityTypes")
+            .and_then(Value::as_string)
+            .and_then(|name| security_type_from_name(name, false));
+        let hidden = entry.get("Hidden").and_then(Value::as_boolean).unwrap_or(false);
+        // The EAP settings of enterprise networks are not stored in the plist.
+        networks.push(WifiNetwork::new(ssid, None, security_type, hidden, None));
     }
+    Ok(networks)
+}
 
-    let interfaces_stdout = String::from_utf8_lossy(&interfaces_output.stdout);
-    let mut wifi_interface: Option<String> = None;
+// Maps a spairport_security_mode value of system_profiler (e.g. "spairport_security_mode_wpa2_personal").
+fn security_type_from_profiler_mode(mode: &str) -> Option<SecurityType> {
+    let name = match mode.strip_prefix("spairport_security_mode_")? {
+        "wpa_personal_mixed" | "wpa2_personal_mixed" => "WPA/WPA2 Personal".to_string(),
+        "wpa3_transition" => "WPA2/WPA3 Personal".to_string(),
+        "owe_transition" => "OWE".to_string(),
+        other => other.replace('_', " "),
+    };
+    security_type_from_name(&name, false)
+}
 
-    // Parse the output to find the Wi-Fi device.
-    let mut lines = interfaces_stdout.lines();
+// Collects the networks with a security mode from the XML output of `system_profiler SPAirPortDataType -xml`:
+// the current network and the other networks in range of each interface.
+fn collect_profiler_networks(value: &Value, networks: &mut Vec<WifiNetwork>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_profiler_networks(item, networks)),
+        Value::Dictionary(dictionary) => {
+            let name = dictionary.get("_name").and_then(Value::as_string);
+            let mode = dictionary.get("spairport_security_mode").and_then(Value::as_string);
+            if let (Some(ssid), Some(mode)) = (name, mode) {
+                let security_type = security_type_from_profiler_mode(mode);
+                networks.push(WifiNetwork::new(ssid.to_string(), None, security_type, false, None));
+            }
+            dictionary.values().for_each(|value| collect_profiler_networks(value, networks));
+        }
+        _ => {}
+    }
+}
+
+fn parse_system_profiler(output: &str) -> Result<Vec<WifiNetwork>, String> {
+    let value = Value::from_reader(Cursor::new(output.as_bytes())).map_err(|e| format!("Invalid property list: {}", e))?;
+    let mut networks = Vec::new();
+    collect_profiler_networks(&value, &mut networks);
+    Ok(networks)
+}
+
+// Takes the security type and hidden flag of each network from the first details that know them.
+fn apply_details(networks: &mut [WifiNetwork], details: &[WifiNetwork]) {
+    for network in networks.iter_mut() {
+        for detail in details.iter().filter(|detail| detail.ssid == network.ssid) {
+            network.security_type = network.security_type.or(detail.security_type);
+            network.hidden |= detail.hidden;
+        }
+    }
+}
+
+// Returns the security types and hidden flags known to the system: all saved networks from the known-networks plist
+// (if qrlan runs as root), then the networks in range from system_profiler. Both are optional.
+fn network_details(runner: &dyn CommandRunner, known_networks_path: &Path) -> Vec<WifiNetwork> {
+    let mut details = fs::read(known_networks_path)
+        .ok()
+        .and_then(|content| parse_known_networks(&content).ok())
+        .unwrap_or_default();
+    if let Ok(output) = runner.run("system_profiler", &["SPAirPortDataType", "-xml"]) {
+        if output.success() {
+            details.extend(parse_system_profiler(&output.stdout).unwrap_or_default());
+        }
+    }
+    details
+}
+
+// Parses the output of `networksetup -listallhardwareports` and returns the device name of the Wi-Fi port.
+fn parse_wifi_interface(output: &str) -> Option<String> {
+    let mut lines = output.lines();
     while let Some(line) = lines.next() {
         // Look for lines indicating a Wi-Fi or AirPort hardware port.
         if line.contains("Hardware Port: Wi-Fi") || line.contains("Hardware Port: AirPort") {
-            if let Some(device_line) = lines.next() {
-                // The next line should contain the device name (e.g., "Device: en0").
-                if let Some(device_name) = device_line.strip_prefix("Device: ") {
-                    wifi_interface = Some(device_name.trim().to_string());
-                    break; // Found the Wi-Fi interface.
-                }
+            // The next line should contain the device name (e.g., "Device: en0").
+            if let Some(device_name) = lines.next().and_then(|device_line| device_line.strip_prefix("Device: ")) {
+                return Some(device_name.trim().to_string());
             }
         }
     }
+    None
+}
+
+// Finds the Wi-Fi interface device name (e.g., en0, en1).
+fn wifi_interface(runner: &dyn CommandRunner) -> Result<String, String> {
+    let interfaces_output = runner
+        .run("networksetup", &["-listallhardwareports"])
+        .map_err(|e| format!("Failed to execute 'networksetup -listallhardwareports': {}", e))?;
+
+    if !interfaces_output.success() {
+        // Error if the command to list hardware ports fails.
+        return Err(format!(
+            "'networksetup -listallhardwareports' command failed with exit code {}: {}.",
+            interfaces_output.status,
+            interfaces_output.stderr.trim().trim_end_matches('.')
+        ));
+    }
+
+    parse_wifi_interface(&interfaces_output.stdout)
+        .ok_or_else(|| "No active Wi-Fi interface (e.g., en0, en1) could be found.".to_string())
+}
+
+// Parses the output of `networksetup -listpreferredwirelessnetworks`.
+fn parse_preferred_networks(output: &str) -> Vec<WifiNetwork> {
+    output
+        .lines()
+        .skip(1) // Skip the header line (e.g., "Preferred networks on en0:").
+        .map(|line| line.trim()) // Trim whitespace from each line.
+        .filter(|line| !line.is_empty()) // Remove any empty lines.
+        .map(|ssid| WifiNetwork::from_ssid(ssid.to_string()))
+        .collect()
+}
 
-    // Ensure a Wi-Fi interface was found.
-    let interface_name = wifi_interface.ok_or_else(|| "No active Wi-Fi interface (e.g., en0, en1) could be found.".to_string())?;
+/// Returns the preferred networks of the Wi-Fi interface with the security types and hidden flags known to the system.
+/// Passwords are looked up later in the Keychain, only for the selected network, to avoid a prompt per network.
+pub fn get_known_networks(runner: &dyn CommandRunner, known_networks_path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let interface_name = wifi_interface(runner)?;
 
     // List preferred wireless networks for the found Wi-Fi interface.
-    let output = Command::new("networksetup")
-        .arg("-listpreferredwirelessnetworks")
-        .arg(&interface_name) // Specify the Wi-Fi interface device name.
-        .output()
+    let output = runner
+        .run("networksetup", &["-listpreferredwirelessnetworks", &interface_name]) // Specify the Wi-Fi interface device name.
         .map_err(|e| format!("Failed to execute 'networksetup -listpreferredwirelessnetworks': {}", e))?;
 
-    if !output.status.success() {
-        let stderr_str = String::from_utf8_lossy(&output.stderr);
+    if !output.success() {
+        let stderr_str = output.stderr.trim().trim_end_matches('.');
         // Provide a more specific error if the interface is not a Wi-Fi interface.
         if stderr_str.contains("is not a Wi-Fi interface") {
              return Err(format!("The identified network interface '{}' does not appear to be a Wi-Fi interface. Please check your network configuration.", interface_name));
         }
         // General error for other failures.
         return Err(format!(
-            "'networksetup -listpreferredwirelessnetworks' command failed for interface '{}' with status {}: {}.", 
+            "'networksetup -listpreferredwirelessnetworks' command failed for interface '{}' with exit code {}: {}.", 
             interface_name, 
             output.status, 
             stderr_str
         ));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    // Parse the output to extract SSIDs.
-    let networks: Vec<WifiNetwork> = stdout
-        .lines()
-        .skip(1) // Skip the header line (e.g., "Preferred networks on en0:").
-        .map(|line| line.trim()) // Trim whitespace from each line.
-        .filter(|line| !line.is_empty()) // Remove any empty lines.
-        .map(|ssid_str| {
-            let ssid = ssid_str.to_string();
-            // Password and security type are not fetched here to avoid multiple prompts or complex lookups for all networks.
-            // They will be handled for the selected network in main.rs.
-            WifiNetwork { ssid, password: None, security_type: None }
-        })
-        .collect();
-    
+    let mut networks = parse_preferred_networks(&output.stdout);
+    if !networks.is_empty() {
+        apply_details(&mut networks, &network_details(runner, known_networks_path));
+    }
+
     if networks.is_empty() {
         // Inform the user if no preferred networks are found on the interface.
         println!("No preferred Wi-Fi networks found on interface '{}'. You can enter network details manually.", interface_name);
@@ -83,6 +182,33 @@ pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
     Ok(networks)
 }
 
+// Parses the output of `networksetup -getairportnetwork`, e.g. "Current Wi-Fi Network: Home".
+// Returns None for "You are not associated with an AirPort network.".
+fn parse_airport_network(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Current Wi-Fi Network: ").or_else(|| line.strip_prefix("Current AirPort Network: ")))
+        .map(|ssid| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Returns the SSID of the network the Wi-Fi interface is associated with.
+pub fn current_ssid(runner: &dyn CommandRunner) -> Result<Option<String>, String> {
+    let interface_name = wifi_interface(runner)?;
+    let output = runner
+        .run("networksetup", &["-getairportnetwork", &interface_name])
+        .map_err(|e| format!("Failed to execute 'networksetup -getairportnetwork': {}", e))?;
+    if !output.success() {
+        return Err(format!(
+            "'networksetup -getairportnetwork' command failed for interface '{}' with exit code {}: {}.",
+            interface_name,
+            output.status,
+            output.stderr.trim().trim_end_matches('.')
+        ));
+    }
+    Ok(parse_airport_network(&output.stdout))
+}
+
 /// Fetches the stored password for a specific Wi-Fi SSID from the macOS Keychain.
 ///
 /// # Arguments
@@ -92,22 +218,19 @@ pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
 /// * `Ok(Some(String))` if the password is found.
 /// * `Ok(None)` if the password is not found or access is denied.
 /// * `Err(String)` if there's an error executing the `security` command.
-pub fn fetch_password_for_ssid(ssid: &str) -> Result<Option<String>, String> {
+pub fn fetch_password_for_ssid(runner: &dyn CommandRunner, ssid: &str) -> Result<Option<String>, String> {
     // Use the `security` command-line tool to find the generic password for the given SSID.
     // The `-wa` flag specifies that only the password itself should be outputted.
     // The SSID is used as the account name (`-a ssid`) and service name (`-s ssid`) by convention for Wi-Fi passwords.
     // However, `networksetup` stores Wi-Fi passwords with the SSID as the "account" field in Keychain Access
     // when viewed, and the service is "AirPort network password".
     // `find-generic-password -wa <ssid>` seems to work directly in most cases for Wi-Fi passwords.
-    match Command::new("security")
-        .arg("find-generic-password")
-        .arg("-wa") // Output the password only (w: password, a: account name).
-        .arg(ssid)  // The SSID is typically used as the account name for Wi-Fi passwords in Keychain.
-        .output() 
-    {
+    // -wa: Output the password only (w: password, a: account name).
+    // The SSID is typically used as the account name for Wi-Fi passwords in Keychain.
+    match runner.run("security", &["find-generic-password", "-wa", ssid]) {
         Ok(pass_output) => {
-            if pass_output.status.success() {
-                let pass_str = String::from_utf8_lossy(&pass_output.stdout).trim().to_string();
+            if pass_output.success() {
+                let pass_str = pass_output.stdout.trim().to_string();
                 if !pass_str.is_empty() {
                     // Password successfully retrieved.
                     Ok(Some(pass_str))
@@ -128,3 +251,128 @@ pub fn fetch_password_for_ssid(ssid: &str) -> Result<Option<String>, String> {
         }
     }
 }
+
+/// Preferred networks of the Wi-Fi interface, with passwords from the Keychain.
+pub struct MacosSource {
+    runner: Box<dyn CommandRunner>,
+    known_networks_path: PathBuf,
+}
+
+impl MacosSource {
+    pub fn new(runner: Box<dyn CommandRunner>) -> MacosSource {
+        MacosSource { runner, known_networks_path: PathBuf::from(KNOWN_NETWORKS_PATH) }
+    }
+}
+
+impl WifiSource for MacosSource {
+    fn name(&self) -> &str {
+        "macos"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        get_known_networks(self.runner.as_ref(), &self.known_networks_path)
+    }
+
+    fn fetch_password(&self, ssid: &str) -> Result<Option<String>, String> {
+        fetch_password_for_ssid(self.runner.as_ref(), ssid)
+    }
+
+    fn current_ssid(&self) -> Result<Option<String>, String> {
+        current_ssid(self.runner.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::command::ReplayRunner;
+
+    fn known_networks_fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/apple/known-networks.plist")
+    }
+
+    fn replay(fixture: &str) -> MacosSource {
+        MacosSource {
+            known_networks_path: known_networks_fixture(),
+            ..MacosSource::new(Box::new(ReplayRunner::from_fixture(fixture)))
+        }
+    }
+
+    #[test]
+    fn known_networks_of_wifi_interface() {
+        let networks = replay("macos").known_networks().unwrap();
+        let ssids: Vec<&str> = networks.iter().map(|network| network.ssid.as_str()).collect();
+        assert_eq!(ssids, ["Home", "Café", "Hotel Lobby"]);
+
+        // From the known-networks plist.
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wpa2Wpa3Transition));
+        assert!(networks[0].hidden);
+        assert_eq!(networks[1].security_type, Some(SecurityType::Open));
+        // Not saved in the plist, but in range according to system_profiler.
+        assert_eq!(networks[2].security_type, Some(SecurityType::WpaPersonal));
+        assert!(!networks[2].hidden);
+    }
+
+    #[test]
+    fn known_networks_without_readable_plist() {
+        let source = MacosSource {
+            known_networks_path: PathBuf::from("/nonexistent/known-networks.plist"),
+            ..replay("macos")
+        };
+        let networks = source.known_networks().unwrap();
+        assert_eq!(networks[0].security_type, None);
+        assert_eq!(networks[1].security_type, Some(SecurityType::Open));
+    }
+
+    #[test]
+    fn parse_known_networks_plist() {
+        let networks = parse_known_networks(&fs::read(known_networks_fixture()).unwrap()).unwrap();
+        assert_eq!(networks.len(), 3);
+        let corp = networks.iter().find(|network| network.ssid == "Corp").unwrap();
+        assert_eq!(corp.security_type, None); // Enterprise settings are not stored in the plist.
+        assert!(parse_known_networks(b"not a plist").is_err());
+    }
+
+    #[test]
+    fn security_type_from_profiler_modes() {
+        let mode = security_type_from_profiler_mode;
+        assert_eq!(mode("spairport_security_mode_none"), Some(SecurityType::Open));
+        assert_eq!(mode("spairport_security_mode_wep"), Some(SecurityType::Wep));
+        assert_eq!(mode("spairport_security_mode_wpa_personal_mixed"), Some(SecurityType::WpaPersonal));
+        assert_eq!(mode("spairport_security_mode_wpa3_personal"), Some(SecurityType::Wpa3Sae));
+        assert_eq!(mode("spairport_security_mode_wpa3_transition"), Some(SecurityType::Wpa2Wpa3Transition));
+        assert_eq!(mode("spairport_security_mode_owe"), Some(SecurityType::Owe));
+        assert_eq!(mode("spairport_security_mode_wpa2_enterprise"), Some(SecurityType::Enterprise));
+        // Without EAP settings, enterprise networks are listed with an undetermined security type.
+        let network = WifiNetwork::new("Corp".to_string(), None, mode("spairport_security_mode_wpa2_enterprise"), false, None);
+        assert_eq!(network.security_type, None);
+        assert_eq!(mode("wpa2_personal"), None);
+    }
+
+    #[test]
+    fn known_networks_fails_without_wifi_interface() {
+        let error = replay("macos_no_wifi").known_networks().unwrap_err();
+        assert!(error.starts_with("No active Wi-Fi interface"), "{}", error);
+    }
+
+    #[test]
+    fn current_ssid_of_wifi_interface() {
+        assert_eq!(replay("macos").current_ssid().unwrap().as_deref(), Some("Café"));
+        assert_eq!(parse_airport_network("You are not associated with an AirPort network.\n"), None);
+    }
+
+    #[test]
+    fn fetch_password_from_keychain() {
+        let source = replay("macos");
+        assert_eq!(source.fetch_password("Home").unwrap().as_deref(), Some("correct horse battery"));
+        // Denied access and missing items are not errors; the password is entered manually instead.
+        assert_eq!(source.fetch_password("Café").unwrap(), None);
+        assert_eq!(source.fetch_password("Hotel Lobby").unwrap(), None);
+        // The security command itself is missing.
+        assert!(replay("macos_no_wifi").fetch_password("Home").is_err());
+    }
+}
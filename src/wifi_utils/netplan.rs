@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+// Returns a scalar as a string. Unquoted SSIDs or passwords such as 1234 are parsed as numbers by YAML.
+fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+// Maps the auth.key-management setting to a security type. Without it, access points with a password use PSK.
+fn parse_key_management(key_management: Option<&str>, has_password: bool) -> Option<SecurityType> {
+    match key_management.map(str::to_lowercase).as_deref() {
+        None if has_password => Some(SecurityType::WpaPersonal),
+        None | Some("none") => Some(SecurityType::Open),
+        Some("psk") => Some(SecurityType::WpaPersonal),
+        Some("sae") => Some(SecurityType::Wpa3Sae),
+        Some("eap") | Some("eap-sha256") | Some("eap-suite-b-192") | Some("802.1x") => Some(SecurityType::Enterprise),
+        _ => None,
+    }
+}
+
+// Converts one entry of an access-points mapping.
+fn parse_access_point(ssid: String, access_point: &Value) -> WifiNetwork {
+    // Open networks are often written without settings ("Guest": {} or just "Guest":).
+    let auth = access_point.get("auth");
+    let setting = |value: Option<&Value>, key: &str| value.and_then(|v| v.get(key)).and_then(scalar_string).filter(|s| !s.is_empty());
+    let password = setting(Some(access_point), "password").or_else(|| setting(auth, "password"));
+    let security_type = parse_key_management(setting(auth, "key-management").as_deref(), password.is_some());
+    let hidden = access_point.get("hidden").and_then(Value::as_bool).unwrap_or(false);
+
+    let enterprise = match security_type {
+        Some(SecurityType::Enterprise) => setting(auth, "method").and_then(|method| EapMethod::from_name(&method)).map(|eap_method| {
+            EnterpriseCredentials {
+                eap_method,
+                phase2_method: setting(auth, "phase2-auth").and_then(|phase2| Phase2Method::from_name(&phase2)),
+                identity: setting(auth, "identity"),
+                anonymous_identity: setting(auth, "anonymous-identity"),
+            }
+        }),
+        _ => None,
+    };
+    WifiNetwork::new(ssid, password, security_type, hidden, enterprise)
+}
+
+/// Parses the access points of all Wi-Fi interfaces (network.wifis.<iface>.access-points) of a netplan file.
+pub fn parse_config(content: &str) -> Result<Vec<WifiNetwork>, String> {
+    let document: Value = serde_yaml::from_str(content).map_err(|e| format!("Invalid netplan YAML: {}", e))?;
+    let Some(wifis) = document.get("network").and_then(|network| network.get("wifis")).and_then(Value::as_mapping) else {
+        return Ok(Vec::new()); // Files configuring only ethernet or bridges.
+    };
+
+    let mut networks = Vec::new();
+    for interface in wifis.values() {
+        let Some(access_points) = interface.get("access-points").and_then(Value::as_mapping) else {
+            continue;
+        };
+        for (ssid, access_point) in access_points {
+            if let Some(ssid) = scalar_string(ssid).filter(|ssid| !ssid.is_empty()) {
+                networks.push(parse_access_point(ssid, access_point));
+            }
+        }
+    }
+    Ok(networks)
+}
+
+/// Reads a netplan file, or all *.yaml files of a directory such as /etc/netplan.
+/// Like netplan, later files override access points of earlier files with the same SSID.
+pub fn read_config(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .map_err(|e| format!("Failed to read netplan directory '{}': {}", path.display(), e))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().map(|e| e == "yaml" || e == "yml").unwrap_or(false))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut networks: Vec<WifiNetwork> = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(&file).map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => format!(
+                "Permission denied reading netplan file '{}'. Run qrlan with sudo to read it.",
+                file.display()
+            ),
+            _ => format!("Failed to read netplan file '{}': {}", file.display(), e),
+        })?;
+        for network in parse_config(&content).map_err(|e| format!("{} ({})", e.trim_end_matches('.'), file.display()))? {
+            match networks.iter_mut().find(|known| known.ssid == network.ssid) {
+                Some(known) => *known = network,
+                None => networks.push(network),
+            }
+        }
+    }
+    Ok(networks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_config_merges_fixture_directory() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/netplan");
+        let networks = read_config(&root).unwrap();
+        assert_eq!(networks.len(), 4);
+
+        // Overridden by 90-kiosk.yaml.
+        assert_eq!(networks[0].ssid, "Kiosk");
+        assert_eq!(networks[0].password.as_deref(), Some("new password"));
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wpa3Sae));
+
+        assert_eq!(networks[1].ssid, "Guest");
+        assert_eq!(networks[1].security_type, Some(SecurityType::Open));
+        assert!(networks[1].hidden);
+
+        assert_eq!(networks[2].ssid, "1234");
+        assert_eq!(networks[2].password.as_deref(), Some("12345678"));
+        assert_eq!(networks[2].security_type, Some(SecurityType::WpaPersonal));
+
+        let credentials = networks[3].enterprise.as_ref().unwrap();
+        assert_eq!(networks[3].ssid, "Corp");
+        assert_eq!(credentials.eap_method, EapMethod::Ttls);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Pap));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(networks[3].password.as_deref(), Some("s3cret"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::ini;
+use super::linux::{parse_enterprise_credentials, parse_key_mgmt};
+use super::{SecurityType, WifiNetwork, WifiSource};
+
+/// Directory where NetworkManager stores its keyfile connection profiles.
+const DEFAULT_KEYFILE_ROOT: &str = "/etc/NetworkManager/system-connections";
+/// Environment variable overriding the keyfile directory, e.g. of a mounted disk image.
+/// When set, the keyfiles are read directly instead of asking NetworkManager (see NmcliSource).
+const KEYFILE_ROOT_ENV: &str = "QRLAN_NM_ROOT";
+
+// Returns the keyfile directory, honoring the QRLAN_NM_ROOT override.
+fn keyfile_root() -> PathBuf {
+    std::env::var_os(KEYFILE_ROOT_ENV).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_KEYFILE_ROOT))
+}
+
+/// Returns true if the keyfile directory was overridden with QRLAN_NM_ROOT.
+pub fn is_root_overridden() -> bool {
+    std::env::var_os(KEYFILE_ROOT_ENV).is_some()
+}
+
+/// Returns true if the keyfile directory exists and can be read (usually requires root).
+pub fn is_available() -> bool {
+    fs::read_dir(keyfile_root()).is_ok()
+}
+
+// Removes the escapes of GKeyFile values (\s, \n, \t, \r, \\).
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+// Decodes the ssid setting. SSIDs that are not valid UTF-8 are stored as a list of bytes ("72;111;109;101;").
+fn decode_ssid(value: &str) -> String {
+    let bytes: Option<Vec<u8>> = value.trim_end_matches(';').split(';').map(|byte| byte.parse::<u8>().ok()).collect();
+    match bytes {
+        Some(bytes) if value.contains(';') => String::from_utf8_lossy(&bytes).into_owned(),
+        _ => value.to_string(),
+    }
+}
+
+// Converts the settings of one keyfile. Returns None for connections that are not Wi-Fi connections.
+fn parse_keyfile(settings: &HashMap<String, String>) -> Option<WifiNetwork> {
+    // Settings may use the short ("wifi") or the full ("802-11-wireless") section names.
+    let get = |sections: &[&str], key: &str| {
+        sections
+            .iter()
+            .find_map(|section| settings.get(&format!("{}.{}", section, key)))
+            .map(|value| unescape_value(value))
+            .unwrap_or_default()
+    };
+    let wifi = ["wifi", "802-11-wireless"];
+    let security = ["wifi-security", "802-11-wireless-security"];
+    let dot1x = ["802-1x"];
+
+    let connection_type = get(&["connection"], "type");
+    if connection_type != "wifi" && connection_type != "802-11-wireless" {
+        return None;
+    }
+    let ssid = match get(&wifi, "ssid") {
+        ssid if ssid.is_empty() => get(&["connection"], "id"),
+        ssid => decode_ssid(&ssid),
+    };
+    if ssid.is_empty() {
+        return None;
+    }
+
+    let key_mgmt = get(&security, "key-mgmt");
+    let wep_key = get(&security, &format!("wep-key{}", get(&security, "wep-tx-keyidx").parse::<u8>().unwrap_or(0)));
+    // Connections without a [wifi-security] section are open.
+    let security_type = parse_key_mgmt(&key_mgmt, !wep_key.is_empty());
+
+    let enterprise = if security_type == Some(SecurityType::Enterprise) {
+        parse_enterprise_credentials(
+            &get(&dot1x, "eap").replace(';', ","),
+            &get(&dot1x, "phase2-auth"),
+            &get(&dot1x, "identity"),
+            &get(&dot1x, "anonymous-identity"),
+        )
+    } else {
+        None
+    };
+    let secret = match security_type {
+        Some(SecurityType::Wep) => wep_key,
+        Some(SecurityType::Enterprise) => get(&dot1x, "password"),
+        _ => get(&security, "psk"), // Empty if the secret is owned by an agent (psk-flags=1).
+    };
+
+    let password = if secret.is_empty() { None } else { Some(secret) };
+    Some(WifiNetwork::new(ssid, password, security_type, get(&wifi, "hidden") == "true", enterprise))
+}
+
+/// Reads all Wi-Fi connections from a directory of NetworkManager keyfiles (*.nmconnection).
+pub fn read_keyfiles(root: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let entries = fs::read_dir(root).map_err(|e| match e.kind() {
+        ErrorKind::PermissionDenied => format!(
+            "Permission denied reading NetworkManager keyfiles in '{}'. Run qrlan with sudo to read them.",
+            root.display()
+        ),
+        _ => format!("Failed to read NetworkManager keyfile directory '{}': {}", root.display(), e),
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|e| e == "nmconnection").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut networks = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read NetworkManager keyfile '{}': {}", path.display(), e))?;
+        networks.extend(parse_keyfile(&ini::parse_sections(&content)));
+    }
+    Ok(networks)
+}
+
+/// Returns the Wi-Fi connections of the NetworkManager keyfiles in
+/// /etc/NetworkManager/system-connections (or $QRLAN_NM_ROOT).
+pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
+    let networks = read_keyfiles(&keyfile_root())?;
+    if networks.is_empty() {
+        println!("No Wi-Fi connections found in the NetworkManager keyfiles. You can enter network details manually.");
+    }
+    Ok(networks)
+}
+
+/// NetworkManager keyfiles, read directly. Used if nmcli fails, e.g. because the service is not running.
+pub struct KeyfileSource;
+
+impl WifiSource for KeyfileSource {
+    fn name(&self) -> &str {
+        "nm-keyfile"
+    }
+
+    fn is_available(&self) -> bool {
+        is_available()
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        get_known_networks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::{EapMethod, Phase2Method};
+
+    fn find<'a>(networks: &'a [WifiNetwork], ssid: &str) -> &'a WifiNetwork {
+        networks.iter().find(|network| network.ssid == ssid).expect("network from fixture")
+    }
+
+    #[test]
+    fn decode_ssid_handles_byte_lists() {
+        assert_eq!(decode_ssid("Home"), "Home");
+        assert_eq!(decode_ssid("72;111;109;101;"), "Home");
+    }
+
+    #[test]
+    fn read_keyfiles_parses_fixtures() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/nm_keyfile");
+        let networks = read_keyfiles(&root).unwrap();
+        assert_eq!(networks.len(), 3); // The ethernet connection and the unrelated file are skipped.
+
+        let home = find(&networks, "Home Net");
+        assert_eq!(home.password.as_deref(), Some("correct horse"));
+        assert_eq!(home.security_type, Some(SecurityType::WpaPersonal));
+
+        let guest = find(&networks, "Guest");
+        assert_eq!(guest.security_type, Some(SecurityType::Open));
+        assert!(guest.hidden);
+
+        let corp = find(&networks, "Corp");
+        let credentials = corp.enterprise.as_ref().unwrap();
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(corp.password.as_deref(), Some("s3cret"));
+    }
+}
@@ -0,0 +1,297 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+use super::command::SystemRunner;
+use super::WifiNetwork;
+
+/// A source of known Wi-Fi networks: a system backend such as NetworkManager, or a configuration file.
+pub trait WifiSource {
+    /// Name of the source, as used with `--source`.
+    fn name(&self) -> &str;
+
+    /// Returns true if the source exists on this system (e.g. the tool is installed or the file exists).
+    fn is_available(&self) -> bool;
+
+    /// Returns the networks of the source.
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String>;
+
+    /// Looks up the password of a network that was listed without one, e.g. in a keychain.
+    /// Sources that list passwords along with the networks return Ok(None).
+    fn fetch_password(&self, _ssid: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    /// Returns the SSID of the network the system is currently connected to.
+    /// Sources that cannot tell (e.g. configuration files) return Ok(None).
+    fn current_ssid(&self) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+}
+
+/// A configuration file (or directory) read by one of the importers.
+pub struct FileSource {
+    name: &'static str,
+    path: PathBuf,
+    reader: fn(&Path) -> Result<Vec<WifiNetwork>, String>,
+}
+
+impl FileSource {
+    pub fn new(name: &'static str, path: impl Into<PathBuf>, reader: fn(&Path) -> Result<Vec<WifiNetwork>, String>) -> FileSource {
+        FileSource { name, path: path.into(), reader }
+    }
+}
+
+impl WifiSource for FileSource {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn is_available(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        (self.reader)(&self.path)
+    }
+}
+
+/// Returns the sources of this platform in probing order.
+/// System backends come first, followed by configuration files at their default locations.
+pub fn registry() -> Vec<Box<dyn WifiSource>> {
+    #[allow(unused_mut)] // Empty on platforms without a backend.
+    let mut sources: Vec<Box<dyn WifiSource>> = Vec::new();
+    #[cfg(target_os = "macos")]
+    sources.push(Box::new(super::macos::MacosSource::new(Box::new(SystemRunner))));
+    #[cfg(target_os = "windows")]
+    sources.push(Box::new(super::windows::NetshSource::new(Box::new(SystemRunner))));
+    #[cfg(target_os = "linux")]
+    {
+        sources.push(Box::new(super::linux::NmcliSource::new(Box::new(SystemRunner))));
+        sources.push(Box::new(super::nm_keyfile::KeyfileSource));
+        sources.push(Box::new(super::iwd::IwdSource));
+        sources.push(Box::new(FileSource::new("netplan", "/etc/netplan", super::netplan::read_config)));
+        sources.push(Box::new(FileSource::new(
+            "wpa_supplicant",
+            super::wpa_supplicant::system_config_path(),
+            super::wpa_supplicant::read_config,
+        )));
+        sources.push(Box::new(FileSource::new("uci", "/etc/config/wireless", super::uci::read_config)));
+        sources.push(Box::new(FileSource::new("hostapd", "/etc/hostapd/hostapd.conf", super::hostapd::read_config)));
+    }
+    sources
+}
+
+/// Returns the source of the registry with the given name.
+pub fn find_source(name: &str) -> Result<Box<dyn WifiSource>, String> {
+    let sources = registry();
+    let names: Vec<String> = sources.iter().map(|source| source.name().to_string()).collect();
+    sources
+        .into_iter()
+        .find(|source| source.name().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Unknown source '{}'. Available sources: {}", name.trim(), names.join(", ")))
+}
+
+/// Reads the networks of the first available source that can be read and returns it along with its networks.
+/// Sources that fail are reported and skipped. A source without networks is only used if no later source has any.
+pub fn probe(sources: Vec<Box<dyn WifiSource>>) -> Result<(Box<dyn WifiSource>, Vec<WifiNetwork>), String> {
+    let names: Vec<String> = sources.iter().map(|source| source.name().to_string()).collect();
+    let mut last_error = None;
+    let mut fallback = None;
+    for source in sources.into_iter().filter(|source| source.is_available()) {
+        if let Some(error) = last_error.take() {
+            eprintln!("{}. Trying {} instead.", error, source.name());
+        }
+        match source.known_networks() {
+            Ok(networks) if !networks.is_empty() => return Ok((source, networks)),
+            Ok(networks) => {
+                if fallback.is_none() {
+                    fallback = Some((source, networks));
+                }
+            }
+            Err(e) => last_error = Some(format!("{}: {}", source.name(), e.trim_end_matches('.'))),
+        }
+    }
+    if let Some(fallback) = fallback {
+        if let Some(error) = last_error {
+            eprintln!("{}.", error);
+        }
+        return Ok(fallback);
+    }
+    Err(last_error.unwrap_or_else(|| {
+        if names.is_empty() {
+            "Wi-Fi network retrieval is not implemented for this operating system".to_string()
+        } else {
+            format!("None of the Wi-Fi network sources is available ({})", names.join(", "))
+        }
+    }))
+}
+
+/// Reads the networks of all given sources and merges them. Fails if one of the sources fails.
+pub fn read_sources(sources: &[Box<dyn WifiSource>]) -> Result<Vec<WifiNetwork>, String> {
+    let mut lists = Vec::new();
+    for source in sources {
+        lists.push(source.known_networks().map_err(|e| format!("{}: {}", source.name(), e.trim_end_matches('.')))?);
+    }
+    Ok(merge_networks(lists))
+}
+
+/// Merges the networks of several sources. Networks with the same SSID are combined into one,
+/// taking each missing detail (password, security type, EAP settings) from the later duplicates.
+pub fn merge_networks(lists: impl IntoIterator<Item = Vec<WifiNetwork>>) -> Vec<WifiNetwork> {
+    let mut merged: Vec<WifiNetwork> = Vec::new();
+    for network in lists.into_iter().flatten() {
+        match merged.iter_mut().find(|known| known.ssid == network.ssid) {
+            Some(known) => {
+                known.password = known.password.take().or(network.password);
+                known.security_type = known.security_type.or(network.security_type);
+                known.enterprise = known.enterprise.take().or(network.enterprise);
+                known.hidden |= network.hidden;
+            }
+            None => merged.push(network),
+        }
+    }
+    merged
+}
+
+// Returns the first value found in the available sources. Errors are only reported if no source has a value.
+fn first_found(
+    sources: &[Box<dyn WifiSource>],
+    lookup: impl Fn(&dyn WifiSource) -> Result<Option<String>, String>,
+) -> Result<Option<String>, String> {
+    let mut first_error = None;
+    for source in sources.iter().filter(|source| source.is_available()) {
+        match lookup(source.as_ref()) {
+            Ok(Some(value)) => return Ok(Some(value)),
+            Ok(None) => {}
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map_or(Ok(None), Err)
+}
+
+/// Looks up the password of a network in the given sources. Returns the first password found.
+pub fn fetch_password(sources: &[Box<dyn WifiSource>], ssid: &str) -> Result<Option<String>, String> {
+    first_found(sources, |source| source.fetch_password(ssid))
+}
+
+/// Returns the SSID of the network the system is connected to, as reported by the first source that knows it.
+pub fn current_ssid(sources: &[Box<dyn WifiSource>]) -> Result<Option<String>, String> {
+    first_found(sources, |source| source.current_ssid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::SecurityType;
+
+    struct FakeSource {
+        name: &'static str,
+        available: bool,
+        networks: Result<Vec<WifiNetwork>, String>,
+        current: Result<Option<String>, String>,
+    }
+
+    impl WifiSource for FakeSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_available(&self) -> bool {
+            self.available
+        }
+
+        fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+            self.networks.clone()
+        }
+
+        fn current_ssid(&self) -> Result<Option<String>, String> {
+            self.current.clone()
+        }
+    }
+
+    fn fake(name: &'static str, available: bool, networks: Result<Vec<WifiNetwork>, String>) -> Box<dyn WifiSource> {
+        Box::new(FakeSource { name, available, networks, current: Ok(None) })
+    }
+
+    fn connected(name: &'static str, current: Result<Option<String>, String>) -> Box<dyn WifiSource> {
+        Box::new(FakeSource { name, available: true, networks: Ok(Vec::new()), current })
+    }
+
+    fn network(ssid: &str, password: Option<&str>, security_type: Option<SecurityType>) -> WifiNetwork {
+        WifiNetwork {
+            password: password.map(str::to_string),
+            security_type,
+            ..WifiNetwork::from_ssid(ssid.to_string())
+        }
+    }
+
+    #[test]
+    fn probe_skips_unavailable_and_failing_sources() {
+        let sources = vec![
+            fake("missing", false, Ok(vec![network("Unused", None, None)])),
+            fake("broken", true, Err("service not running".to_string())),
+            fake("working", true, Ok(vec![network("Home", Some("secret"), None)])),
+            fake("later", true, Ok(vec![network("Later", None, None)])),
+        ];
+        let (source, networks) = probe(sources).unwrap();
+        assert_eq!(source.name(), "working");
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].ssid, "Home");
+    }
+
+    #[test]
+    fn probe_skips_empty_sources() {
+        let sources = vec![
+            fake("empty", true, Ok(Vec::new())),
+            fake("working", true, Ok(vec![network("Home", None, None)])),
+        ];
+        let (source, networks) = probe(sources).unwrap();
+        assert_eq!(source.name(), "working");
+        assert_eq!(networks[0].ssid, "Home");
+
+        let sources = vec![
+            fake("first", true, Ok(Vec::new())),
+            fake("second", true, Ok(Vec::new())),
+            fake("broken", true, Err("service not running".to_string())),
+        ];
+        let (source, networks) = probe(sources).unwrap();
+        assert_eq!(source.name(), "first");
+        assert!(networks.is_empty());
+    }
+
+    #[test]
+    fn probe_reports_last_error() {
+        let error = probe(vec![fake("broken", true, Err("service not running".to_string()))]).err().unwrap();
+        assert_eq!(error, "broken: service not running");
+        assert!(probe(vec![fake("missing", false, Ok(Vec::new()))]).is_err());
+    }
+
+    #[test]
+    fn read_sources_merges_duplicates() {
+        let sources = vec![
+            fake("first", true, Ok(vec![network("Home", None, Some(SecurityType::WpaPersonal)), network("Lab", None, None)])),
+            fake("second", true, Ok(vec![network("Home", Some("secret"), Some(SecurityType::Wpa3Sae))])),
+        ];
+        let networks = read_sources(&sources).unwrap();
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0].password.as_deref(), Some("secret"));
+        assert_eq!(networks[0].security_type, Some(SecurityType::WpaPersonal)); // The first source wins.
+
+        let failing = vec![fake("broken", true, Err("unreadable".to_string()))];
+        assert_eq!(read_sources(&failing).err().as_deref(), Some("broken: unreadable"));
+    }
+
+    #[test]
+    fn current_ssid_of_first_source_that_knows_it() {
+        let sources = vec![
+            connected("broken", Err("service not running".to_string())),
+            fake("file", true, Ok(Vec::new())),
+            connected("backend", Ok(Some("Home".to_string()))),
+        ];
+        assert_eq!(current_ssid(&sources).unwrap().as_deref(), Some("Home"));
+        assert_eq!(current_ssid(&sources[..1]).err().as_deref(), Some("service not running"));
+        assert_eq!(current_ssid(&sources[1..2]).unwrap(), None);
+    }
+}
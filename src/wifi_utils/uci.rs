@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+// Splits a UCI line into words. Words may be quoted with '...' (literal) or "..." (with backslash escapes).
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '#' {
+            break; // Comment until the end of the line.
+        }
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            chars.next();
+            match c {
+                '\'' => word.extend(chars.by_ref().take_while(|&c| c != '\'')),
+                '"' => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => word.extend(chars.next()),
+                            other => word.push(other),
+                        }
+                    }
+                }
+                '\\' => word.extend(chars.next()),
+                other => word.push(other),
+            }
+        }
+        words.push(word);
+    }
+    words
+}
+
+// Parses the sections of a UCI file into (section type, options). Lists keep their last value.
+fn parse_sections(content: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut sections: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in content.lines() {
+        let words = split_words(line);
+        match words.as_slice() {
+            [keyword, section_type, ..] if keyword == "config" => sections.push((section_type.clone(), HashMap::new())),
+            [keyword, name, value, ..] if keyword == "option" || keyword == "list" => {
+                if let Some((_, options)) = sections.last_mut() {
+                    options.insert(name.clone(), value.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    sections
+}
+
+// Maps the encryption option (e.g. "psk2+ccmp", "sae-mixed", "owe") to a security type.
+// The cipher suffix after '+' does not affect the QR code.
+fn parse_encryption(encryption: &str) -> Option<SecurityType> {
+    let mode = encryption.split('+').next().unwrap_or("").to_lowercase();
+    match mode.as_str() {
+        "" | "none" => Some(SecurityType::Open),
+        "owe" => Some(SecurityType::Owe),
+        "wep" | "wep-open" | "wep-shared" => Some(SecurityType::Wep),
+        "psk" | "psk2" | "psk-mixed" => Some(SecurityType::WpaPersonal),
+        "sae" => Some(SecurityType::Wpa3Sae),
+        "sae-mixed" => Some(SecurityType::Wpa2Wpa3Transition),
+        "wpa" | "wpa2" | "wpa3" | "wpa-mixed" | "wpa3-mixed" | "wpa3-192" => Some(SecurityType::Enterprise),
+        _ => None,
+    }
+}
+
+// Returns the WEP key of an interface. The key option is either the key itself or the index (1-4) of a keyN option.
+// Keys prefixed with "s:" are ASCII keys, others hex keys.
+fn wep_key(options: &HashMap<String, String>) -> Option<String> {
+    let key = options.get("key")?;
+    let key = match key.as_str() {
+        "1" | "2" | "3" | "4" => options.get(&format!("key{}", key))?,
+        _ => key,
+    };
+    Some(key.strip_prefix("s:").unwrap_or(key).to_string()).filter(|key| !key.is_empty())
+}
+
+// Builds the 802.1X credentials of a client (mode sta) interface. Access points authenticate against
+// a RADIUS server, so their EAP method is unknown.
+fn parse_enterprise_credentials(options: &HashMap<String, String>) -> Option<EnterpriseCredentials> {
+    let string = |key: &str| options.get(key).filter(|value| !value.is_empty()).cloned();
+    let eap_method = EapMethod::from_name(options.get("eap_type")?)?;
+    // auth is e.g. "MSCHAPV2" or "EAP-MSCHAPV2".
+    let phase2_method = string("auth").and_then(|auth| Phase2Method::from_name(auth.trim_start_matches("EAP-").trim_start_matches("eap-")));
+    Some(EnterpriseCredentials {
+        eap_method,
+        phase2_method,
+        identity: string("identity"),
+        anonymous_identity: string("anonymous_identity"),
+    })
+}
+
+// Converts the options of one wifi-iface section. Returns None for disabled and non-AP/client interfaces.
+fn parse_interface(options: &HashMap<String, String>) -> Option<WifiNetwork> {
+    if options.get("disabled").map(|value| value == "1").unwrap_or(false) {
+        return None;
+    }
+    let mode = options.get("mode").map(String::as_str).unwrap_or("ap");
+    if mode != "ap" && mode != "sta" {
+        return None; // Mesh, ad-hoc and monitor interfaces cannot be joined with a QR code.
+    }
+    let ssid = options.get("ssid").filter(|ssid| !ssid.is_empty())?.clone();
+    let hidden = options.get("hidden").map(|value| value == "1").unwrap_or(false);
+    let security_type = parse_encryption(options.get("encryption").map(String::as_str).unwrap_or("none"));
+
+    let (password, enterprise) = match security_type {
+        Some(SecurityType::Wep) => (wep_key(options), None),
+        Some(SecurityType::Enterprise) => {
+            (options.get("password").filter(|p| !p.is_empty()).cloned(), parse_enterprise_credentials(options))
+        }
+        // SAE interfaces use the same key option as PSK interfaces.
+        _ => (options.get("key").filter(|key| !key.is_empty()).cloned(), None),
+    };
+    Some(WifiNetwork::new(ssid, password, security_type, hidden, enterprise))
+}
+
+/// Parses the wifi-iface sections of an OpenWrt wireless configuration (/etc/config/wireless).
+pub fn parse_config(content: &str) -> Vec<WifiNetwork> {
+    parse_sections(content)
+        .iter()
+        .filter(|(section_type, _)| section_type == "wifi-iface")
+        .filter_map(|(_, options)| parse_interface(options))
+        .collect()
+}
+
+/// Reads the networks of an OpenWrt wireless configuration file.
+pub fn read_config(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read UCI configuration '{}': {}", path.display(), e))?;
+    Ok(parse_config(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIRELESS_CONFIG: &str = r#"
+config wifi-device 'radio0'
+	option type 'mac80211'
+	option channel '36'
+
+config wifi-iface 'default_radio0'
+	option device 'radio0'
+	option network 'lan'
+	option mode 'ap'
+	option ssid 'Branch Office'
+	option encryption 'sae-mixed'
+	option key 'it''s secret'
+
+config wifi-iface 'guest'
+	option device 'radio0'
+	option ssid "Guest \"Wi-Fi\""
+	option encryption 'psk2+ccmp'
+	option key 'welcome1'
+	option hidden '1'
+
+config wifi-iface 'old'
+	option ssid Legacy
+	option encryption wep-open
+	option key 2
+	option key2 's:abcde'
+
+config wifi-iface 'off'
+	option ssid 'Disabled'
+	option disabled '1'
+
+config wifi-iface 'mesh'
+	option mode 'mesh'
+	option mesh_id 'backhaul'
+	option ssid 'Backhaul'
+"#;
+
+    #[test]
+    fn parse_config_reads_wifi_interfaces() {
+        let networks = parse_config(WIRELESS_CONFIG);
+        assert_eq!(networks.len(), 3); // Disabled and mesh interfaces are skipped.
+
+        assert_eq!(networks[0].ssid, "Branch Office");
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wpa2Wpa3Transition));
+        assert_eq!(networks[0].password.as_deref(), Some("its secret"));
+
+        assert_eq!(networks[1].ssid, "Guest \"Wi-Fi\"");
+        assert_eq!(networks[1].security_type, Some(SecurityType::WpaPersonal));
+        assert_eq!(networks[1].password.as_deref(), Some("welcome1"));
+        assert!(networks[1].hidden);
+
+        assert_eq!(networks[2].security_type, Some(SecurityType::Wep));
+        assert_eq!(networks[2].password.as_deref(), Some("abcde"));
+    }
+}
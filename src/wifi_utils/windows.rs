@@ -1,152 +1,167 @@
-use std::process::Command;
-use super::WifiNetwork;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use super::command::CommandRunner;
+use super::{windows_profile, WifiNetwork, WifiSource};
+
+// Exports the WLAN profiles (all, or only the one with the given name) as WLANProfile XML and parses them.
+// The XML export is language-independent, unlike the localized text of 'netsh wlan show profile'.
+// Passwords are exported in clear text with key=clear; for profiles of all users this requires administrator rights.
+fn export_profiles(runner: &dyn CommandRunner, name: Option<&str>) -> Result<Vec<WifiNetwork>, String> {
+    // Each export gets its own directory, so concurrent exports (e.g. in tests) do not read each other's files.
+    static EXPORT_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let export_dir = std::env::temp_dir().join(format!(
+        "qrlan_netsh_export_{}_{}",
+        std::process::id(),
+        EXPORT_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&export_dir)
+        .map_err(|e| format!("Failed to create temporary directory '{}': {}", export_dir.display(), e))?;
+
+    let name_arg = name.map(|name| format!("name={}", name));
+    let folder_arg = format!("folder={}", export_dir.display());
+    let mut args = vec!["wlan", "export", "profile"];
+    args.extend(name_arg.as_deref());
+    args.push("key=clear");
+    args.push(&folder_arg);
+
+    let result = runner
+        .run("netsh", &args)
+        .map_err(|e| format!("Failed to execute 'netsh wlan export profile'. Is WLAN AutoConfig service running? Error: {}", e))
+        .and_then(|output| {
+            if output.success() {
+                windows_profile::read_profiles(&export_dir)
+            } else {
+                // netsh reports most errors on stdout.
+                let message = if output.stderr.trim().is_empty() { &output.stdout } else { &output.stderr };
+                Err(format!("'netsh wlan export profile' command failed with exit code {}: {}", output.status, message.trim()))
+            }
+        });
 
-// Include the constants generated by build.rs
-include!(concat!(env!("OUT_DIR"), "/generated_translations.rs"));
+    fs::remove_dir_all(&export_dir).ok(); // Cleanup is not critical.
+    result
+}
+
+pub fn get_known_networks(runner: &dyn CommandRunner) -> Result<Vec<WifiNetwork>, String> {
+    let networks = export_profiles(runner, None)?;
+    if networks.is_empty() {
+         println!("No Wi-Fi profiles found using 'netsh', or unable to retrieve their details. You can enter network details manually.");
+    }
+    Ok(networks)
+}
 
-// Helper function to check if a line part matches any keyword in a list (case-insensitive for the line_key_part)
-fn line_key_matches_any(line_key_part: &str, keywords: &[&str]) -> bool {
-    let trimmed_lower_key = line_key_part.trim().to_lowercase();
-    keywords.iter().any(|&kw| kw.to_lowercase() == trimmed_lower_key)
+pub fn fetch_password_for_ssid(runner: &dyn CommandRunner, ssid: &str) -> Result<Option<String>, String> {
+    let networks = export_profiles(runner, Some(ssid))
+        .map_err(|e| format!("{}. Administrator rights might be required.", e.trim_end_matches('.')))?;
+    Ok(networks.into_iter().find(|network| network.ssid == ssid).and_then(|network| network.password))
 }
 
-pub fn get_known_networks() -> Result<Vec<WifiNetwork>, String> {
-    let output = Command::new("netsh")
-        .args(&["wlan", "show", "profiles"])
-        .output()
-        .map_err(|e| format!("Failed to execute 'netsh wlan show profiles'. Is WLAN AutoConfig service running? Error: {}", e))?;
+// Parses the output of `netsh wlan show interfaces` and returns the SSID of the first connected interface.
+// The labels are localized, but the SSID is labeled "SSID" in all languages and only listed while connected.
+fn parse_interfaces_ssid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(label, _)| label.trim() == "SSID")
+        .map(|(_, ssid)| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
 
-    if !output.status.success() {
-        let error_message = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("'netsh wlan show profiles' command failed with status {}: {}.", output.status, error_message));
+pub fn current_ssid(runner: &dyn CommandRunner) -> Result<Option<String>, String> {
+    let output = runner
+        .run("netsh", &["wlan", "show", "interfaces"])
+        .map_err(|e| format!("Failed to execute 'netsh wlan show interfaces': {}", e))?;
+    if !output.success() {
+        let message = if output.stderr.trim().is_empty() { &output.stdout } else { &output.stderr };
+        return Err(format!("'netsh wlan show interfaces' command failed with exit code {}: {}", output.status, message.trim()));
     }
+    Ok(parse_interfaces_ssid(&output.stdout))
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut networks = Vec::new();
-
-    for line in stdout.lines() {
-        if let Some(colon_pos) = line.rfind(':') { // Find the last colon
-            let potential_key_part = &line[..colon_pos];
-            let potential_ssid_part = &line[colon_pos + 1..];
-
-            // Check if the part before the last colon (trimmed) matches any "All User Profile" keyword
-            if line_key_matches_any(potential_key_part.trim_start(), ALL_USER_PROFILE_KEYWORDS) {
-                let ssid = potential_ssid_part.trim().to_string();
-                // Skip empty or placeholder SSIDs like "<Kein>" (German for <None>) or "<none>"
-                if ssid.is_empty() || ssid.eq_ignore_ascii_case("<Kein>") || ssid.eq_ignore_ascii_case("<none>") {
-                    continue;
-                }
-
-                let profile_output_result = Command::new("netsh")
-                    .args(&["wlan", "show", "profile", &format!("name={}", ssid), "key=clear"])
-                    .output();
-            
-                let mut password = None;
-                let mut security_type = None;
-
-                match profile_output_result {
-                    Ok(prof_out) => {
-                        if prof_out.status.success() {
-                            let profile_details = String::from_utf8_lossy(&prof_out.stdout);
-                            let mut key_content_value: Option<String> = None;
-                            let mut authentication_value: Option<String> = None;
-
-                            for detail_line in profile_details.lines() {
-                                if let Some(colon_pos_detail) = detail_line.find(':') { // Find the first colon
-                                    let key_part = &detail_line[..colon_pos_detail];
-                                    let value_part = &detail_line[colon_pos_detail + 1..];
-
-                                    if line_key_matches_any(key_part, KEY_CONTENT_KEYWORDS) {
-                                        key_content_value = Some(value_part.trim().to_string());
-                                    } else if line_key_matches_any(key_part, AUTHENTICATION_KEYWORDS) && authentication_value.is_none() {
-                                        // Take the first authentication line, as there might be multiple for different ciphers
-                                        authentication_value = Some(value_part.trim().to_uppercase());
-                                    }
-                                }
-                            }
-                        
-                            // Filter out keys that indicate no password or are empty
-                            password = key_content_value.filter(|k| !k.is_empty() && !k.eq_ignore_ascii_case("not present") && !k.eq_ignore_ascii_case("nicht vorhanden"));
-
-                            if let Some(auth_str) = authentication_value {
-                                if auth_str.contains("WPA2PSK") || auth_str.contains("WPAPSK") || auth_str.contains("WPA2-PERSONAL") || auth_str.contains("WPA-PERSONAL") || auth_str.contains("WPA3SAE") || auth_str.contains("WPA3-PERSONAL") {
-                                    security_type = Some("WPA".to_string());
-                                } else if auth_str.contains("WEP") {
-                                    security_type = Some("WEP".to_string());
-                                } else if auth_str.contains("OPEN") {
-                                    security_type = Some("nopass".to_string());
-                                }
-                            }
-                        } else {
-                            // eprintln!("Could not retrieve details for profile '{}' (may require admin rights for password): {}", ssid, String::from_utf8_lossy(&prof_out.stderr));
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to execute 'netsh wlan show profile name={}': {}.", ssid, e);
-                    }
-                }
-                networks.push(WifiNetwork { ssid, password, security_type });
-            }
-        }
+/// WLAN profiles, exported with netsh.
+pub struct NetshSource {
+    runner: Box<dyn CommandRunner>,
+}
+
+impl NetshSource {
+    pub fn new(runner: Box<dyn CommandRunner>) -> NetshSource {
+        NetshSource { runner }
     }
-    if networks.is_empty() {
-         println!("No Wi-Fi profiles found using 'netsh', or unable to retrieve their details. You can enter network details manually.");
+}
+
+impl WifiSource for NetshSource {
+    fn name(&self) -> &str {
+        "netsh"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn known_networks(&self) -> Result<Vec<WifiNetwork>, String> {
+        get_known_networks(self.runner.as_ref())
+    }
+
+    fn fetch_password(&self, ssid: &str) -> Result<Option<String>, String> {
+        fetch_password_for_ssid(self.runner.as_ref(), ssid)
+    }
+
+    fn current_ssid(&self) -> Result<Option<String>, String> {
+        current_ssid(self.runner.as_ref())
     }
-    Ok(networks)
 }
 
-pub fn fetch_password_for_ssid(ssid: &str) -> Result<Option<String>, String> {
-    let profile_output = Command::new("netsh")
-        .args(&["wlan", "show", "profile", &format!("name={}", ssid), "key=clear"])
-        .output()
-        .map_err(|e| format!("Failed to execute 'netsh wlan show profile name={}' key=clear': {}", ssid, e))?;
-
-    if !profile_output.status.success() {
-        let error_message_stderr = String::from_utf8_lossy(&profile_output.stderr).trim().to_string();
-        let error_message_stdout = String::from_utf8_lossy(&profile_output.stdout).trim().to_string();
-
-        let mut detailed_error = String::new();
-        if !error_message_stderr.is_empty() {
-            detailed_error.push_str(&format!("Stderr: {}", error_message_stderr));
-        }
-        // Sometimes errors also end up in stdout
-        if !error_message_stdout.is_empty() {
-            if !detailed_error.is_empty() { 
-                detailed_error.push_str("; "); 
-            }
-            // Show only a part of stdout if it's very long (e.g., the entire profile output without an error)
-            const MAX_STDOUT_ERROR_LEN: usize = 200;
-            if error_message_stdout.len() > MAX_STDOUT_ERROR_LEN {
-                detailed_error.push_str(&format!("Stdout (truncated): {}...", &error_message_stdout[..MAX_STDOUT_ERROR_LEN]));
-            } else {
-                detailed_error.push_str(&format!("Stdout: {}", error_message_stdout));
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi_utils::command::ReplayRunner;
+    use crate::wifi_utils::{EapMethod, SecurityType};
 
-        if detailed_error.is_empty() {
-            detailed_error = format!("Command failed with exit code {:?}, but no specific error message found on stderr or stdout.", profile_output.status.code());
-        }
+    fn replay(fixture: &str) -> NetshSource {
+        NetshSource::new(Box::new(ReplayRunner::from_fixture(fixture)))
+    }
 
-        return Err(format!("'netsh wlan show profile name={} key=clear' command failed: {}. Administrator rights might be required.", ssid, detailed_error));
+    #[test]
+    fn known_networks_from_english_export_without_admin_rights() {
+        let networks = replay("netsh_en").known_networks().unwrap();
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0].ssid, "Home Net");
+        assert_eq!(networks[0].password.as_deref(), Some("correct horse"));
+        assert!(networks[0].hidden);
+        assert_eq!(networks[1].ssid, "Office");
+        assert_eq!(networks[1].password, None); // Protected key.
     }
 
-    let profile_details = String::from_utf8_lossy(&profile_output.stdout);
-    let mut key_content_value: Option<String> = None;
-
-    for detail_line in profile_details.lines() {
-        if let Some(colon_pos) = detail_line.find(':') { // Find the first colon
-            let key_part = &detail_line[..colon_pos];
-            let value_part = &detail_line[colon_pos + 1..];
-
-            if line_key_matches_any(key_part, KEY_CONTENT_KEYWORDS) {
-                let potential_key = value_part.trim().to_string();
-                // Filter out keys that indicate no password or are empty
-                if !potential_key.is_empty() && !potential_key.eq_ignore_ascii_case("not present") && !potential_key.eq_ignore_ascii_case("nicht vorhanden") {
-                    key_content_value = Some(potential_key);
-                    break; 
-                }
-            }
-        }
+    #[test]
+    fn known_networks_from_german_export() {
+        let networks = replay("netsh_de").known_networks().unwrap();
+        let ssids: Vec<&str> = networks.iter().map(|network| network.ssid.as_str()).collect();
+        assert_eq!(ssids, ["Corp", "Home Net"]);
+        assert_eq!(networks[0].security_type, Some(SecurityType::Enterprise));
+        assert_eq!(networks[0].enterprise.as_ref().unwrap().eap_method, EapMethod::Peap);
+    }
+
+    #[test]
+    fn fetch_password_exports_single_profile() {
+        assert_eq!(replay("netsh_de").fetch_password("Home Net").unwrap().as_deref(), Some("correct horse"));
+        assert_eq!(replay("netsh_en").fetch_password("Office").unwrap(), None);
+
+        let error = replay("netsh_en").fetch_password("Hotel").unwrap_err();
+        assert!(error.ends_with("Profile \"Hotel\" is not found on any interface. Administrator rights might be required."), "{}", error);
+    }
+
+    #[test]
+    fn current_ssid_from_localized_interface_list() {
+        assert_eq!(replay("netsh_en").current_ssid().unwrap().as_deref(), Some("Home Net")); // Not the "AP BSSID" line.
+        assert_eq!(replay("netsh_de").current_ssid().unwrap(), None); // Disconnected.
+        assert!(replay("netsh_wlansvc_stopped").current_ssid().is_err());
+    }
+
+    #[test]
+    fn localized_netsh_errors_are_passed_on() {
+        let error = replay("netsh_wlansvc_stopped").known_networks().unwrap_err();
+        assert_eq!(
+            error,
+            "'netsh wlan export profile' command failed with exit code 1: Der automatische WLAN-Konfigurationsdienst (wlansvc) wird nicht ausgeführt."
+        );
     }
-    Ok(key_content_value)
 }
@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use roxmltree::{Document, Node};
+
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+// Returns the first child element with the given name, ignoring XML namespaces.
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+// Follows a path of child element names, e.g. ["MSM", "security", "authEncryption"].
+fn path<'a, 'input>(node: Node<'a, 'input>, names: &[&str]) -> Option<Node<'a, 'input>> {
+    names.iter().try_fold(node, |node, name| child(node, name))
+}
+
+// Returns the trimmed text of the first descendant element with the given name, if it is not empty.
+fn descendant_text(node: Node, name: &str) -> Option<String> {
+    node.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+// Returns the text of an element as a boolean ("true"/"false").
+fn is_true(node: Option<Node>) -> bool {
+    node.and_then(|n| n.text()).map(|text| text.trim().eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+// Maps the <authentication> value of a profile to a security type.
+fn parse_authentication(authentication: &str, transition_mode: bool) -> Option<SecurityType> {
+    match authentication.to_uppercase().as_str() {
+        "OPEN" => Some(SecurityType::Open),
+        "SHARED" => Some(SecurityType::Wep),
+        "OWE" => Some(SecurityType::Owe),
+        "WPAPSK" | "WPA2PSK" => Some(SecurityType::WpaPersonal),
+        "WPA3SAE" if transition_mode => Some(SecurityType::Wpa2Wpa3Transition),
+        "WPA3SAE" => Some(SecurityType::Wpa3Sae),
+        "WPA" | "WPA2" | "WPA3" | "WPA3ENT" | "WPA3ENT192" => Some(SecurityType::Enterprise),
+        _ => None,
+    }
+}
+
+// Builds the 802.1X credentials from the <OneX> element. User credentials are not part of
+// profiles; only the outer identity used for identity privacy can be read.
+fn parse_enterprise_credentials(one_x: Node) -> Option<EnterpriseCredentials> {
+    let eap_method_node = one_x.descendants().find(|n| n.is_element() && n.tag_name().name() == "EapMethod")?;
+    let eap_method = EapMethod::from_type_number(descendant_text(eap_method_node, "Type")?.parse().ok()?)?;
+    let config = one_x.descendants().find(|n| n.is_element() && n.tag_name().name() == "Config");
+
+    let phase2_method = match eap_method {
+        // PEAP nests the inner EAP method (26 = EAP-MSCHAPv2, 6 = EAP-GTC) in its configuration.
+        EapMethod::Peap => config
+            .and_then(|config| config.descendants().filter(|n| n.tag_name().name() == "Type").nth(1))
+            .and_then(|n| n.text())
+            .and_then(|inner_type| match inner_type.trim() {
+                "26" => Some(Phase2Method::Mschapv2),
+                "6" => Some(Phase2Method::Gtc),
+                _ => None,
+            }),
+        // TTLS lists the inner method as an element such as <MSCHAPv2Authentication/>.
+        EapMethod::Ttls => config
+            .and_then(|config| config.descendants().find(|n| n.tag_name().name() == "Phase2Authentication"))
+            .and_then(|phase2| phase2.children().find(|n| n.is_element()))
+            .and_then(|method| Phase2Method::from_name(method.tag_name().name().trim_end_matches("Authentication"))),
+        _ => None,
+    };
+    let anonymous_identity = config.and_then(|config| {
+        descendant_text(config, "AnonymousUserName").or_else(|| descendant_text(config, "AnonymousIdentity"))
+    });
+
+    Some(EnterpriseCredentials { eap_method, phase2_method, identity: None, anonymous_identity })
+}
+
+/// Parses a WLANProfile XML document as exported by `netsh wlan export profile`.
+/// Passwords are only available in exports created with `key=clear`.
+pub fn parse_profile(xml: &str) -> Result<WifiNetwork, String> {
+    let document = Document::parse(xml).map_err(|e| format!("Invalid WLAN profile XML: {}", e))?;
+    let profile = document.root_element();
+    if profile.tag_name().name() != "WLANProfile" {
+        return Err(format!("Expected a WLANProfile document, found <{}>.", profile.tag_name().name()));
+    }
+
+    let ssid_config = child(profile, "SSIDConfig");
+    let ssid_node = ssid_config.and_then(|config| child(config, "SSID"));
+    // <hex> holds the exact SSID bytes; <name> may be missing or lossy for non-UTF-8 SSIDs.
+    let ssid = ssid_node
+        .and_then(|ssid| descendant_text(ssid, "hex"))
+        .and_then(|hex_ssid| hex::decode(hex_ssid).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .or_else(|| ssid_node.and_then(|ssid| descendant_text(ssid, "name")))
+        .or_else(|| descendant_text(profile, "name"))
+        .ok_or("WLAN profile without an SSID.")?;
+    let hidden = is_true(ssid_config.and_then(|config| child(config, "nonBroadcast")));
+
+    let security = path(profile, &["MSM", "security"]);
+    let auth_encryption = security.and_then(|security| child(security, "authEncryption"));
+    let security_type = auth_encryption.and_then(|auth| {
+        let authentication = descendant_text(auth, "authentication")?;
+        parse_authentication(&authentication, is_true(child(auth, "transitionMode")))
+    });
+    // Open authentication with WEP encryption is static WEP.
+    let security_type = match security_type {
+        Some(SecurityType::Open)
+            if auth_encryption.and_then(|auth| descendant_text(auth, "encryption")).as_deref() == Some("WEP") =>
+        {
+            Some(SecurityType::Wep)
+        }
+        other => other,
+    };
+
+    let shared_key = security.and_then(|security| child(security, "sharedKey"));
+    let password = shared_key.and_then(|key| {
+        if is_true(child(key, "protected")) {
+            return None; // Encrypted with DPAPI; only readable on the exporting machine.
+        }
+        let key_type = descendant_text(key, "keyType").unwrap_or_default();
+        // A WPA networkKey is the raw 64-hex PSK, which cannot be converted back to the passphrase.
+        if key_type == "networkKey" && security_type != Some(SecurityType::Wep) {
+            return None;
+        }
+        descendant_text(key, "keyMaterial")
+    });
+
+    let enterprise = match security_type {
+        Some(SecurityType::Enterprise) => security.and_then(|security| child(security, "OneX")).and_then(parse_enterprise_credentials),
+        _ => None,
+    };
+    Ok(WifiNetwork::new(ssid, password, security_type, hidden, enterprise))
+}
+
+/// Reads a WLANProfile XML file, or all *.xml files of a directory.
+pub fn read_profiles(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .map_err(|e| format!("Failed to read WLAN profile directory '{}': {}", path.display(), e))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().map(|e| e.eq_ignore_ascii_case("xml")).unwrap_or(false))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut networks = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read WLAN profile '{}': {}", file.display(), e))?;
+        let network = parse_profile(content.trim_start_matches('\u{feff}'))
+            .map_err(|e| format!("{} ({})", e.trim_end_matches('.'), file.display()))?;
+        networks.push(network);
+    }
+    Ok(networks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/windows")
+    }
+
+    #[test]
+    fn read_profiles_parses_fixture_directory() {
+        let networks = read_profiles(&fixture_root()).unwrap();
+        assert_eq!(networks.len(), 3); // Sorted by file name.
+
+        let home = &networks[1];
+        assert_eq!(home.ssid, "Home Net");
+        assert_eq!(home.password.as_deref(), Some("correct horse"));
+        assert_eq!(home.security_type, Some(SecurityType::Wpa2Wpa3Transition));
+        assert!(home.hidden);
+
+        let corp = &networks[0];
+        let credentials = corp.enterprise.as_ref().unwrap();
+        assert_eq!(corp.security_type, Some(SecurityType::Enterprise));
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.anonymous_identity.as_deref(), Some("anonymous@example.com"));
+
+        let protected = &networks[2];
+        assert_eq!(protected.ssid, "Office");
+        assert_eq!(protected.security_type, Some(SecurityType::WpaPersonal));
+        assert_eq!(protected.password, None);
+    }
+
+    #[test]
+    fn parse_profile_rejects_other_documents() {
+        assert!(parse_profile("<plist/>").is_err());
+        assert!(parse_profile("not xml").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{EapMethod, EnterpriseCredentials, Phase2Method, SecurityType, WifiNetwork};
+
+/// Configuration files checked by the automatic Linux backend, in order.
+#[cfg(target_os = "linux")]
+const SYSTEM_CONFIG_PATHS: [&str; 2] = ["/etc/wpa_supplicant/wpa_supplicant.conf", "/etc/wpa_supplicant.conf"];
+
+/// Returns the system-wide wpa_supplicant configuration: the first one that exists, or the default location.
+#[cfg(target_os = "linux")]
+pub fn system_config_path() -> std::path::PathBuf {
+    SYSTEM_CONFIG_PATHS
+        .iter()
+        .map(std::path::PathBuf::from)
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| std::path::PathBuf::from(SYSTEM_CONFIG_PATHS[0]))
+}
+
+// Decodes a string value: "quoted" text, P"printf-escaped" text or unquoted hex bytes (for ssid and wep keys).
+// Returns None for values that are neither.
+pub(super) fn parse_string_value(value: &str) -> Option<String> {
+    if let Some(quoted) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return Some(quoted.to_string());
+    }
+    if let Some(escaped) = value.strip_prefix("P\"").and_then(|v| v.strip_suffix('"')) {
+        let mut unescaped = String::with_capacity(escaped.len());
+        let mut chars = escaped.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                unescaped.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some('t') => unescaped.push('\t'),
+                Some(other) => unescaped.push(other),
+                None => {}
+            }
+        }
+        return Some(unescaped);
+    }
+    hex::decode(value).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+// Maps the key_mgmt list of a network block to a security type.
+// Without key_mgmt, wpa_supplicant defaults to "WPA-PSK WPA-EAP"; the configured secrets decide.
+fn parse_key_mgmt(settings: &HashMap<String, String>) -> Option<SecurityType> {
+    let key_mgmt = settings.get("key_mgmt").map(|value| value.to_uppercase()).unwrap_or_else(|| "WPA-PSK WPA-EAP".to_string());
+    let methods: Vec<&str> = key_mgmt.split_whitespace().collect();
+    let has = |name: &str| methods.contains(&name);
+
+    let has_psk = has("WPA-PSK") || has("WPA-PSK-SHA256") || has("FT-PSK");
+    let has_sae = has("SAE") || has("FT-SAE");
+    let has_eap = methods.iter().any(|method| method.contains("EAP"));
+    if has_psk && has_sae {
+        Some(SecurityType::Wpa2Wpa3Transition)
+    } else if has_sae {
+        Some(SecurityType::Wpa3Sae)
+    } else if has_psk && (settings.contains_key("psk") || !has_eap) {
+        Some(SecurityType::WpaPersonal)
+    } else if has_eap {
+        Some(SecurityType::Enterprise)
+    } else if has("OWE") {
+        Some(SecurityType::Owe)
+    } else if has("NONE") {
+        // Static WEP networks use key_mgmt=NONE with wep_key settings.
+        if (0..4).any(|i| settings.contains_key(&format!("wep_key{}", i))) {
+            Some(SecurityType::Wep)
+        } else {
+            Some(SecurityType::Open)
+        }
+    } else {
+        None
+    }
+}
+
+// Builds the 802.1X credentials of a network block. Returns None for unsupported EAP methods.
+fn parse_enterprise_credentials(settings: &HashMap<String, String>) -> Option<EnterpriseCredentials> {
+    let string = |key: &str| settings.get(key).and_then(|value| parse_string_value(value)).filter(|value| !value.is_empty());
+    let eap_method = settings.get("eap")?.split_whitespace().find_map(EapMethod::from_name)?;
+    let phase2_method = string("phase2").and_then(|phase2| phase2.split_whitespace().find_map(Phase2Method::from_name));
+
+    Some(EnterpriseCredentials {
+        eap_method,
+        phase2_method,
+        identity: string("identity"),
+        anonymous_identity: string("anonymous_identity"),
+    })
+}
+
+// Converts the settings of one network={...} block.
+fn parse_network(settings: &HashMap<String, String>) -> Option<WifiNetwork> {
+    let ssid = settings.get("ssid").and_then(|value| parse_string_value(value))?;
+    let string = |key: &str| settings.get(key).and_then(|value| parse_string_value(value)).filter(|value| !value.is_empty());
+    let security_type = parse_key_mgmt(settings);
+
+    // Unquoted psk values are raw 64-hex PSKs, which cannot be converted back to the passphrase.
+    let passphrase = settings.get("psk").and_then(|psk| psk.strip_prefix('"')).and_then(|psk| psk.strip_suffix('"')).map(str::to_string);
+    let (password, enterprise) = match security_type {
+        Some(SecurityType::Wep) => {
+            // wep_keyN is either "ASCII text" or unquoted hex digits.
+            let index = settings.get("wep_tx_keyidx").and_then(|index| index.parse::<u8>().ok()).unwrap_or(0);
+            let key = settings.get(&format!("wep_key{}", index)).map(|key| key.trim_matches('"').to_string());
+            (key.filter(|key| !key.is_empty()), None)
+        }
+        // sae_password is used for SAE instead of psk if set; transition networks share the psk.
+        Some(SecurityType::Wpa3Sae) => (string("sae_password").or(passphrase), None),
+        Some(SecurityType::Enterprise) => {
+            (string("password").filter(|p| !p.starts_with("hash:")), parse_enterprise_credentials(settings))
+        }
+        _ => (passphrase, None),
+    };
+    let hidden = settings.get("scan_ssid").map(|value| value == "1").unwrap_or(false);
+    Some(WifiNetwork::new(ssid, password, security_type, hidden, enterprise))
+}
+
+/// Parses the network={...} blocks of a wpa_supplicant configuration.
+pub fn parse_config(content: &str) -> Vec<WifiNetwork> {
+    let mut networks = Vec::new();
+    let mut block: Option<HashMap<String, String>> = None;
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match block {
+            None if line.replace(' ', "") == "network={" => block = Some(HashMap::new()),
+            None => {} // Global settings such as ctrl_interface or country.
+            Some(ref mut settings) if line == "}" => {
+                networks.extend(parse_network(settings));
+                block = None;
+            }
+            Some(ref mut settings) => {
+                if let Some((key, value)) = line.split_once('=') {
+                    settings.insert(key.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+        }
+    }
+    networks
+}
+
+/// Reads the networks of a wpa_supplicant configuration file.
+pub fn read_config(path: &Path) -> Result<Vec<WifiNetwork>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read wpa_supplicant configuration '{}': {}", path.display(), e))?;
+    Ok(parse_config(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_fixture() -> Vec<WifiNetwork> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/wpa_supplicant/wpa_supplicant.conf");
+        read_config(&path).unwrap()
+    }
+
+    fn find<'a>(networks: &'a [WifiNetwork], ssid: &str) -> &'a WifiNetwork {
+        networks.iter().find(|network| network.ssid == ssid).unwrap_or_else(|| panic!("network '{}' not found", ssid))
+    }
+
+    #[test]
+    fn parse_string_value_decodes_quoted_hex_and_printf_values() {
+        assert_eq!(parse_string_value("\"Home Net\"").as_deref(), Some("Home Net"));
+        assert_eq!(parse_string_value("486f6d65").as_deref(), Some("Home"));
+        assert_eq!(parse_string_value(r#"P"a\tb\\c\"d\n""#).as_deref(), Some("a\tb\\c\"d\n"));
+        assert_eq!(parse_string_value("not hex"), None);
+        assert_eq!(parse_string_value("ff"), None); // Not valid UTF-8.
+    }
+
+    #[test]
+    fn read_config_parses_fixture() {
+        let networks = read_fixture();
+        assert_eq!(networks.len(), 6);
+
+        let home = find(&networks, "Home Net");
+        assert_eq!(home.security_type, Some(SecurityType::WpaPersonal));
+        assert_eq!(home.password.as_deref(), Some("correct horse"));
+        assert!(!home.hidden);
+
+        let cafe = find(&networks, "Café"); // Hex SSID.
+        assert_eq!(cafe.security_type, Some(SecurityType::WpaPersonal));
+        assert_eq!(cafe.password, None); // Raw PSKs cannot be converted back to the passphrase.
+
+        let lab = find(&networks, "Lab\tA\"1\""); // printf-escaped SSID.
+        assert_eq!(lab.security_type, Some(SecurityType::WpaPersonal)); // Default key_mgmt with a psk.
+        assert_eq!(lab.password.as_deref(), Some("s3cret"));
+        assert!(lab.hidden);
+
+        let legacy = find(&networks, "Legacy");
+        assert_eq!(legacy.security_type, Some(SecurityType::Wep));
+        assert_eq!(legacy.password.as_deref(), Some("0102030405")); // wep_key1, selected by wep_tx_keyidx.
+
+        let guest = find(&networks, "Guest");
+        assert_eq!(guest.security_type, Some(SecurityType::Open));
+        assert_eq!(guest.password, None);
+
+        let corp = find(&networks, "Corp");
+        assert_eq!(corp.security_type, Some(SecurityType::Enterprise));
+        assert_eq!(corp.password.as_deref(), Some("s3cret"));
+        let credentials = corp.enterprise.as_ref().unwrap();
+        assert_eq!(credentials.eap_method, EapMethod::Peap);
+        assert_eq!(credentials.phase2_method, Some(Phase2Method::Mschapv2));
+        assert_eq!(credentials.identity.as_deref(), Some("alice"));
+        assert_eq!(credentials.anonymous_identity.as_deref(), Some("anonymous@example.com"));
+    }
+
+    #[test]
+    fn hex_wep_key_is_encoded_as_hex() {
+        let legacy = read_fixture().into_iter().find(|network| network.ssid == "Legacy").unwrap();
+        let data = crate::qr_generator::generate_qr_code_data(
+            &legacy.ssid,
+            legacy.password.as_deref().unwrap(),
+            legacy.security_type.unwrap(),
+            legacy.hidden,
+            None,
+        );
+        assert_eq!(data, "WIFI:S:Legacy;T:WEP;P:0102030405;;");
+    }
+
+    #[test]
+    fn parse_config_defaults_key_mgmt_by_secret() {
+        // Without key_mgmt and psk, the WPA-EAP default applies.
+        let networks = parse_config("network={\n ssid=\"Corp\"\n eap=TTLS\n identity=\"bob\"\n}\n");
+        assert_eq!(networks[0].security_type, Some(SecurityType::Enterprise));
+        assert_eq!(networks[0].enterprise.as_ref().unwrap().eap_method, EapMethod::Ttls);
+
+        // Unsupported EAP methods leave the type undetermined.
+        let networks = parse_config("network={\n ssid=\"Corp\"\n key_mgmt=WPA-EAP\n eap=MD5\n}\n");
+        assert_eq!(networks[0].security_type, None);
+
+        let networks = parse_config("network={\n ssid=\"Lab\"\n key_mgmt=SAE\n sae_password=\"sae pw\"\n psk=\"psk pw\"\n}\n");
+        assert_eq!(networks[0].security_type, Some(SecurityType::Wpa3Sae));
+        assert_eq!(networks[0].password.as_deref(), Some("sae pw"));
+    }
+
+    #[test]
+    fn read_config_reports_missing_file() {
+        let error = read_config(Path::new("/nonexistent/wpa_supplicant.conf")).unwrap_err();
+        assert!(error.starts_with("Failed to read wpa_supplicant configuration '/nonexistent/wpa_supplicant.conf'"), "{}", error);
+    }
+}